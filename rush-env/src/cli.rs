@@ -4,8 +4,11 @@ use crate::cli::dot_zshrc::generate_dot_zshrc;
 use clap::{Parser, Subcommand};
 use color_eyre::Result;
 use color_eyre::eyre::WrapErr;
+use rush_env::core::dotfile::DotfileStatus;
+use rush_env::core::rush::Rush;
+use rush_env::shell_env::ShellEnv;
 use std::fs::OpenOptions;
-use std::io::{Error, ErrorKind};
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Parser)]
@@ -20,10 +23,14 @@ pub struct Cli {
 pub enum SubCmd {
     Init,
     Install,
+    /// 按 <dotfile> 放置配置文件，已存在的不同文件会先备份
+    Link,
+    /// 列出 <dotfile> 的 src 与 dest 是否一致
+    Status,
 }
 
 impl SubCmd {
-    pub fn execute(self, rush_dir: impl AsRef<Path>, executable: impl AsRef<Path>) -> Result<()> {
+    pub fn execute(self, rush: &Rush, rush_dir: impl AsRef<Path>, _executable: impl AsRef<Path>) -> Result<()> {
         match self {
             SubCmd::Init => {
                 // 这里可以添加初始化逻辑
@@ -31,10 +38,42 @@ impl SubCmd {
             SubCmd::Install => {
                 // 这里可以添加安装逻辑
             }
+            SubCmd::Link => Self::link(rush, rush_dir)?,
+            SubCmd::Status => Self::status(rush, rush_dir)?,
         }
         Ok(())
     }
 
+    pub fn link(rush: &Rush, rush_dir: impl AsRef<Path>) -> Result<()> {
+        let env = ShellEnv::from_process();
+        for dotfile in rush.dotfiles.iter() {
+            let dest = dotfile.dest(&env)?;
+            let status = dotfile.status(&rush_dir, &env)?;
+            if matches!(status, DotfileStatus::Synced | DotfileStatus::Skipped) {
+                println!("{status:<10} {}", dest.display());
+                continue;
+            }
+            match dotfile
+                .link(&rush_dir, &env)
+                .wrap_err_with(|| format!("Failed to link {}", dest.display()))?
+            {
+                Some(backup) => println!("{:<10} {} (backup: {})", "linked", dest.display(), backup.display()),
+                None => println!("{:<10} {}", "linked", dest.display()),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn status(rush: &Rush, rush_dir: impl AsRef<Path>) -> Result<()> {
+        let env = ShellEnv::from_process();
+        for dotfile in rush.dotfiles.iter() {
+            let status = dotfile.status(&rush_dir, &env)?;
+            println!("{status:<10} {} -> {}", dotfile.src, dotfile.dest(&env)?.display());
+        }
+        Ok(())
+    }
+
+    #[allow(dead_code)]
     pub fn init(rush_dir: impl AsRef<Path>, executable: impl AsRef<Path>) -> Result<()> {
         let home = std::env::var("HOME").wrap_err("HOME environment variable must be set")?;
         let home_path = Path::new(&home);
@@ -47,7 +86,7 @@ impl SubCmd {
                 if matches!(e.kind(), ErrorKind::AlreadyExists) {
                     eprintln!("Warning: .zshrc already exists.");
                 }
-                Err(e)
+                Err(e.into())
             }
         }
     }
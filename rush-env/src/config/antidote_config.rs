@@ -1 +1,72 @@
-pub struct Antidote {}
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Antidote {
+    #[serde(rename = "@work_dir")]
+    pub work_dir: String,
+    #[serde(rename = "bundle", default)]
+    pub bundles: Vec<Bundle>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Bundle {
+    #[serde(rename = "@repo")]
+    pub repo: String,
+    #[serde(rename = "@branch", default)]
+    pub branch: Option<String>,
+    #[serde(rename = "@path", default)]
+    pub path: Option<String>,
+    #[serde(rename = "@kind", default)]
+    pub kind: BundleKind,
+}
+
+/// antidote 的 `kind:` 注解
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleKind {
+    #[default]
+    Zsh,
+    Fpath,
+    Path,
+    Clone,
+    Defer,
+    Autoload,
+}
+
+impl Antidote {
+    /// `.zsh_plugins.txt` 的完整内容，每个 bundle 一行
+    pub fn bundle_file(&self) -> String {
+        self.bundles.iter().map(|bundle| format!("{}\n", bundle.to_line())).collect()
+    }
+}
+
+impl Bundle {
+    pub fn to_line(&self) -> String {
+        let mut line = self.repo.clone();
+        if let Some(branch) = &self.branch {
+            line.push_str(&format!(" branch:{branch}"));
+        }
+        if let Some(path) = &self.path {
+            line.push_str(&format!(" path:{path}"));
+        }
+        if self.kind != BundleKind::Zsh {
+            line.push_str(&format!(" kind:{}", self.kind.as_str()));
+        }
+        line
+    }
+}
+
+impl BundleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BundleKind::Zsh => "zsh",
+            BundleKind::Fpath => "fpath",
+            BundleKind::Path => "path",
+            BundleKind::Clone => "clone",
+            BundleKind::Defer => "defer",
+            BundleKind::Autoload => "autoload",
+        }
+    }
+}
@@ -1,12 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// 代理配置，XML 中的读写由 [`Proxy`](crate::core::proxy::Proxy) 负责
+#[derive(Default, Debug, Clone)]
 pub struct ProxyConfig {
     pub host_url: String,
     pub http_proxy_port: u16,
     pub socks_proxy_port: u16,
+    pub no_proxy: Vec<String>,
+    pub tools: Vec<ProxyTool>,
+    pub wraps: Vec<String>,
     pub http_proxy: String,
     pub https_proxy: String,
     pub all_proxy: String,
 }
 
+/// 不读取 `HTTP_PROXY` 等通用变量、需要单独配置代理的工具
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyTool {
+    Git,
+    Npm,
+    Pip,
+    Cargo,
+}
+
 impl ProxyConfig {
     pub fn new(host_url: String, http_proxy_port: u16, socks_proxy_port: u16) -> Self {
         let http_proxy = format!("http://{host_url}:{http_proxy_port}");
@@ -17,9 +34,77 @@ impl ProxyConfig {
             host_url,
             http_proxy_port,
             socks_proxy_port,
+            no_proxy: vec![],
+            tools: vec![],
+            wraps: vec![],
             http_proxy,
             https_proxy,
             all_proxy,
         }
     }
+
+    pub fn with_no_proxy(mut self, no_proxy: Vec<String>) -> Self {
+        self.no_proxy = no_proxy;
+        self
+    }
+
+    pub fn with_tools(mut self, tools: Vec<ProxyTool>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn with_wraps(mut self, wraps: Vec<String>) -> Self {
+        self.wraps = wraps;
+        self
+    }
+
+    /// 代理相关的环境变量，大写在前、小写在后，`NO_PROXY` 为空时省略
+    pub fn variables(&self) -> Vec<(String, String)> {
+        let mut pairs = vec![
+            ("HTTP_PROXY", self.http_proxy.clone()),
+            ("HTTPS_PROXY", self.https_proxy.clone()),
+            ("ALL_PROXY", self.all_proxy.clone()),
+        ];
+        if !self.no_proxy.is_empty() {
+            pairs.push(("NO_PROXY", self.no_proxy.join(",")));
+        }
+        pairs
+            .into_iter()
+            .flat_map(|(name, value)| [(name.to_string(), value.clone()), (name.to_lowercase(), value)])
+            .collect()
+    }
+}
+
+impl ProxyTool {
+    pub fn command(&self) -> &'static str {
+        match self {
+            ProxyTool::Git => "git",
+            ProxyTool::Npm => "npm",
+            ProxyTool::Pip => "pip",
+            ProxyTool::Cargo => "cargo",
+        }
+    }
+
+    /// 工具专属的代理环境变量
+    pub fn variables(&self, config: &ProxyConfig) -> Vec<(String, String)> {
+        let pairs = match self {
+            ProxyTool::Git => vec![],
+            ProxyTool::Npm => vec![
+                ("npm_config_proxy", config.http_proxy.clone()),
+                ("npm_config_https_proxy", config.https_proxy.clone()),
+            ],
+            ProxyTool::Pip => vec![("PIP_PROXY", config.http_proxy.clone())],
+            ProxyTool::Cargo => vec![("CARGO_HTTP_PROXY", config.http_proxy.clone())],
+        };
+        pairs.into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+    }
+
+    /// 只能通过命令行参数传入代理的工具，返回需要插入在子命令之前的参数；
+    /// git 的 HTTPS 请求同样读取 `http.proxy`，没有 `https.proxy` 这个配置项
+    pub fn arguments(&self, config: &ProxyConfig) -> Option<String> {
+        match self {
+            ProxyTool::Git => Some(format!(r#"-c http.proxy="{}""#, config.http_proxy)),
+            _ => None,
+        }
+    }
 }
@@ -1,3 +1,4 @@
+pub mod antidote;
 pub mod platform;
 pub mod plugin;
 pub mod proxy;
@@ -7,3 +8,4 @@ pub mod language;
 pub mod condition;
 pub mod path;
 pub mod tool;
+pub mod dotfile;
@@ -0,0 +1,101 @@
+use crate::config::antidote_config::Antidote;
+use crate::core::script::export::ExportScript;
+use crate::visitor::{Visit, Visitor, VisitorError};
+
+impl Antidote {
+    pub fn tag() -> &'static str {
+        "<antidote work_dir>"
+    }
+}
+
+impl Visit for Antidote {
+    /// 由生成的脚本在 shell 启动时写入 `.zsh_plugins.txt`，生成过程不改动文件系统；
+    /// 内容未变化时不改动文件，避免触发静态 bundle 的重新生成。
+    /// 重新生成时与旧模板的 `pr antidote load` 一样通过 `<proxy>` 的 `pr` 克隆仓库，未定义 `pr` 时直接执行
+    fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        ExportScript::export(&mut context.env, "ANTIDOTE_DIR", &self.work_dir, writer)?;
+        writeln!(writer, r#"zsh_plugins="${{ANTIDOTE_DIR}}/.zsh_plugins""#)?;
+        writeln!(writer, r#"zsh_plugins_txt="$(cat <<'RUSH_BUNDLES'"#)?;
+        write!(writer, "{}", self.bundle_file())?;
+        writeln!(writer, "RUSH_BUNDLES")?;
+        writeln!(writer, r#")""#)?;
+        writeln!(
+            writer,
+            r#"if [[ ! -f "${{zsh_plugins}}.txt" || "$(<"${{zsh_plugins}}.txt")" != "${{zsh_plugins_txt}}" ]]; then"#
+        )?;
+        writeln!(
+            writer,
+            r#"    mkdir -p "${{ANTIDOTE_DIR}}" && print -r -- "${{zsh_plugins_txt}}" >|"${{zsh_plugins}}.txt""#
+        )?;
+        writeln!(writer, "fi")?;
+        writeln!(writer, r#"if [[ ! "${{zsh_plugins}}.zsh" -nt "${{zsh_plugins}}.txt" ]]; then"#)?;
+        writeln!(
+            writer,
+            r#"    (source "${{ANTIDOTE_DIR}}/.antidote/antidote.zsh" && ${{functions[pr]:+pr}} antidote bundle <"${{zsh_plugins}}.txt" >|"${{zsh_plugins}}.zsh")"#
+        )?;
+        writeln!(writer, "fi")?;
+        writeln!(writer, r#"source "${{zsh_plugins}}.zsh""#)?;
+        writeln!(writer, "unset zsh_plugins zsh_plugins_txt")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_file() {
+        let xml = r#"
+            <antidote work_dir="/tmp/antidote">
+                <bundle repo="zsh-users/zsh-autosuggestions"/>
+                <bundle repo="ohmyzsh/ohmyzsh" path="plugins/git"/>
+                <bundle repo="zsh-users/zsh-completions" branch="develop" kind="fpath"/>
+                <bundle repo="zdharma-continuum/fast-syntax-highlighting" kind="defer"/>
+            </antidote>
+        "#;
+        let antidote: Antidote = quick_xml::de::from_str(xml).unwrap();
+        #[rustfmt::skip]
+        let expected =
+r#"zsh-users/zsh-autosuggestions
+ohmyzsh/ohmyzsh path:plugins/git
+zsh-users/zsh-completions branch:develop kind:fpath
+zdharma-continuum/fast-syntax-highlighting kind:defer
+"#;
+        assert_eq!(expected, antidote.bundle_file());
+    }
+
+    #[test]
+    fn test_visit_emits_bundle_file() {
+        let work_dir = std::env::temp_dir().join(format!("rush-antidote-{}", std::process::id()));
+        let antidote = Antidote {
+            work_dir: work_dir.to_str().unwrap().to_string(),
+            bundles: vec![
+                quick_xml::de::from_str(r#"<bundle repo="romkatv/zsh-defer"/>"#).unwrap(),
+                quick_xml::de::from_str(r#"<bundle repo="ohmyzsh/ohmyzsh" path="plugins/git"/>"#).unwrap(),
+            ],
+        };
+        let mut buf = Vec::new();
+        antidote.visit(&mut Visitor::default(), &mut buf).unwrap();
+        assert!(!work_dir.exists());
+        let output = String::from_utf8(buf).unwrap();
+        #[rustfmt::skip]
+        let expected =
+r#"zsh_plugins="${ANTIDOTE_DIR}/.zsh_plugins"
+zsh_plugins_txt="$(cat <<'RUSH_BUNDLES'
+romkatv/zsh-defer
+ohmyzsh/ohmyzsh path:plugins/git
+RUSH_BUNDLES
+)"
+if [[ ! -f "${zsh_plugins}.txt" || "$(<"${zsh_plugins}.txt")" != "${zsh_plugins_txt}" ]]; then
+    mkdir -p "${ANTIDOTE_DIR}" && print -r -- "${zsh_plugins_txt}" >|"${zsh_plugins}.txt"
+fi
+if [[ ! "${zsh_plugins}.zsh" -nt "${zsh_plugins}.txt" ]]; then
+    (source "${ANTIDOTE_DIR}/.antidote/antidote.zsh" && ${functions[pr]:+pr} antidote bundle <"${zsh_plugins}.txt" >|"${zsh_plugins}.zsh")
+fi
+source "${zsh_plugins}.zsh"
+unset zsh_plugins zsh_plugins_txt
+"#;
+        assert!(output.ends_with(expected), "{output}");
+    }
+}
@@ -1,6 +1,11 @@
 use crate::core::platform::Platform;
+use crate::shell_env::ShellEnv;
+use crate::visitor::VisitorError;
 use derive_more::{AsMut, AsRef, Deref, DerefMut};
 use serde::{Deserialize, Deserializer, Serialize};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Default, Debug, Clone, Serialize)]
 #[derive(AsRef, AsMut, Deref, DerefMut)]
@@ -30,45 +35,162 @@ pub enum Predicate {
     LinkExists(String),
     #[serde(rename = "platform")]
     Platform(Platform),
+    /// 只能用于 `<proxy>` 的条件，在 shell 启动时探测；其他元素的条件在生成时求值，结果会被固化在脚本中
+    #[serde(rename = "port_open")]
+    PortOpen(PortOpen),
     #[default]
     None,
 }
 
 impl Predicate {
-    pub fn check(&self) -> bool {
+    /// 在模拟环境 `env` 中判断，`<has>` 查找其中的 PATH，路径中的变量以其中的值展开
+    pub fn check(&self, env: &ShellEnv) -> bool {
         match self {
-            Predicate::All(conditions) => conditions.iter().all(Predicate::check),
-            Predicate::Any(conditions) => conditions.iter().any(Predicate::check),
-            Predicate::Not(condition) => !condition.check(),
-            Predicate::Has(command) => Self::has_command(command),
-            Predicate::FileExists(path) => Self::file_exists(path),
-            Predicate::DirExists(path) => Self::dir_exists(path),
-            Predicate::LinkExists(path) => Self::link_exists(path),
+            Predicate::All(conditions) => conditions.iter().all(|predicate| predicate.check(env)),
+            Predicate::Any(conditions) => conditions.iter().any(|predicate| predicate.check(env)),
+            Predicate::Not(condition) => !condition.0.check(env),
+            Predicate::Has(command) => env.which(command).is_some(),
+            Predicate::FileExists(path) => Self::expand_path(env, "<file_exists>", path).is_some_and(|path| path.is_file()),
+            Predicate::DirExists(path) => Self::expand_path(env, "<dir_exists>", path).is_some_and(|path| path.is_dir()),
+            Predicate::LinkExists(path) => Self::expand_path(env, "<link_exists>", path).is_some_and(|path| path.is_symlink()),
             Predicate::Platform(platform) => platform.contains_current(),
+            Predicate::PortOpen(port_open) => port_open.check(),
             Predicate::None => true,
         }
     }
 
-    fn has_command(command: &str) -> bool {
-        which::which(command).is_ok()
+    /// 是否含有只能在 shell 运行时求值的 `<port_open>`
+    pub fn is_runtime(&self) -> bool {
+        match self {
+            Predicate::All(conditions) | Predicate::Any(conditions) => conditions.iter().any(Predicate::is_runtime),
+            Predicate::Not(condition) => condition.is_runtime(),
+            Predicate::PortOpen(_) => true,
+            _ => false,
+        }
     }
 
-    fn file_exists(path: &str) -> bool {
-        std::path::Path::new(path).is_file()
+    /// 渲染为 shell 中的判断：`<port_open>` 由 [`PortOpen::PROBE`] 在 shell 运行时探测，
+    /// 其余条件在生成时求值为 `true`/`false` 并参与化简，不含运行时探测时结果只会是 `true` 或 `false`
+    pub fn shell_test(&self, env: &ShellEnv) -> String {
+        let constant = |value: bool| if value { "true" } else { "false" }.to_string();
+        match self {
+            Predicate::All(conditions) => {
+                let tests = conditions.iter().map(|predicate| predicate.shell_test(env)).collect::<Vec<_>>();
+                if tests.iter().any(|test| test == "false") {
+                    return constant(false);
+                }
+                Self::group(tests.into_iter().filter(|test| test != "true").collect(), "&&", "true")
+            }
+            Predicate::Any(conditions) => {
+                let tests = conditions.iter().map(|predicate| predicate.shell_test(env)).collect::<Vec<_>>();
+                if tests.iter().any(|test| test == "true") {
+                    return constant(true);
+                }
+                Self::group(tests.into_iter().filter(|test| test != "false").collect(), "||", "false")
+            }
+            Predicate::Not(condition) => match condition.shell_test(env).as_str() {
+                "true" => constant(false),
+                "false" => constant(true),
+                test => format!("! {test}"),
+            },
+            Predicate::PortOpen(port_open) => port_open.shell_test(),
+            predicate => constant(predicate.check(env)),
+        }
     }
 
-    fn dir_exists(path: &str) -> bool {
-        std::path::Path::new(path).is_dir()
+    fn group(tests: Vec<String>, operator: &str, empty: &str) -> String {
+        match tests.len() {
+            0 => empty.to_string(),
+            1 => tests.into_iter().next().unwrap_or_default(),
+            _ => format!("{{ {}; }}", tests.join(&format!(" {operator} "))),
+        }
     }
 
-    fn link_exists(path: &str) -> bool {
-        std::path::Path::new(path).is_symlink()
+    /// 展开路径中的 `~` 与 `${VAR}`，无法展开的路径视为不存在
+    fn expand_path(env: &ShellEnv, tag: &'static str, path: &str) -> Option<PathBuf> {
+        env.expand_path(tag, path).ok()
     }
 }
 
-impl Condition {
+/// TCP 端口探测：在超时时间内能建立连接即视为可达
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PortOpen {
+    #[serde(rename = "@host", default = "PortOpen::default_host")]
+    pub host: String,
+    #[serde(rename = "@port")]
+    pub port: u16,
+    #[serde(
+        rename = "@timeout_ms",
+        default = "PortOpen::default_timeout_ms",
+        deserialize_with = "PortOpen::deserialize_timeout_ms"
+    )]
+    pub timeout_ms: u64,
+}
+
+impl PortOpen {
+    /// 生成的脚本中用于运行时探测端口的函数名
+    pub const PROBE: &'static str = "rush_port_open";
+
+    /// 运行时探测函数：`rush_port_open host port timeout_centiseconds`。
+    /// 在后台用 zsh 的 `ztcp` 连接，每 10ms 检查一次，超时后终止连接并返回失败，shell 启动最多因此等待约 `timeout_ms`
+    pub const PROBE_BODY: &'static str = r#"
+setopt local_options no_monitor no_notify
+zmodload zsh/net/tcp zsh/zselect 2>/dev/null || return 1
+{ ztcp "$1" "$2" && ztcp -c "$REPLY"; } 2>/dev/null &
+local pid=$! waited=0
+while kill -0 $pid 2>/dev/null; do
+    if (( waited >= $3 )); then
+        kill $pid 2>/dev/null
+        return 1
+    fi
+    zselect -t 1
+    (( waited++ ))
+done
+wait $pid
+"#;
+
+    fn default_host() -> String {
+        "127.0.0.1".to_string()
+    }
+
+    fn default_timeout_ms() -> u64 {
+        50
+    }
+
+    /// 超时为 0 时连接必然失败，视为配置错误
+    fn deserialize_timeout_ms<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match u64::deserialize(deserializer)? {
+            0 => Err(serde::de::Error::custom("timeout_ms must be greater than 0")),
+            timeout_ms => Ok(timeout_ms),
+        }
+    }
+
     pub fn check(&self) -> bool {
-        self.0.check()
+        let timeout = Duration::from_millis(self.timeout_ms);
+        match (self.host.as_str(), self.port).to_socket_addrs() {
+            Ok(addrs) => addrs.into_iter().any(|addr| TcpStream::connect_timeout(&addr, timeout).is_ok()),
+            Err(_) => false,
+        }
+    }
+
+    /// 探测函数以 10ms 为单位计时，向上取整
+    fn shell_test(&self) -> String {
+        let centiseconds = self.timeout_ms.div_ceil(10);
+        format!("{} {} {} {centiseconds}", Self::PROBE, self.host, self.port)
+    }
+}
+
+impl Condition {
+    /// 在生成时求值；含有 `<port_open>` 时报错，它只能用于在 shell 启动时探测的 `<proxy>` 条件
+    pub fn check(&self, env: &ShellEnv) -> Result<bool, VisitorError> {
+        if self.0.is_runtime() {
+            return Err(VisitorError::RuntimeCondition);
+        }
+        Ok(self.0.check(env))
     }
 }
 
@@ -99,3 +221,90 @@ impl<'de> Deserialize<'de> for Conditions {
         Ok(Conditions(List::deserialize(deserializer)?.element))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_port_open_with_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let xml = format!(r#"<condition><port_open host="127.0.0.1" port="{port}" timeout_ms="50"/></condition>"#);
+        let condition: Condition = quick_xml::de::from_str(&xml).unwrap();
+        assert!(condition.0.check(&ShellEnv::default()));
+    }
+
+    #[test]
+    fn test_port_open_without_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let xml = format!(r#"<condition><not><port_open port="{port}"/></not></condition>"#);
+        let condition: Condition = quick_xml::de::from_str(&xml).unwrap();
+        assert!(condition.0.check(&ShellEnv::default()));
+    }
+
+    #[test]
+    fn test_shell_test() {
+        let env = ShellEnv::new([("PATH", "/rush/no/such/dir")]);
+        let test = |xml: &str| quick_xml::de::from_str::<Condition>(xml).unwrap().shell_test(&env);
+        assert_eq!("true", Condition::default().shell_test(&env));
+        assert_eq!("false", test("<condition><has>sh</has></condition>"));
+        assert_eq!(
+            "rush_port_open 127.0.0.1 7890 5",
+            test(r#"<condition><port_open port="7890"/></condition>"#)
+        );
+        assert_eq!(
+            "! rush_port_open ::1 80 250",
+            test(r#"<condition><not><port_open host="::1" port="80" timeout_ms="2500"/></not></condition>"#)
+        );
+        assert_eq!(
+            "false",
+            test(r#"<condition><all><has>sh</has><port_open port="1"/></all></condition>"#)
+        );
+        assert_eq!(
+            "{ rush_port_open 127.0.0.1 1 5 || rush_port_open 127.0.0.1 2 5; }",
+            test(r#"<condition><any><has>sh</has><port_open port="1"/><port_open port="2"/></any></condition>"#)
+        );
+    }
+
+    #[test]
+    fn test_path_predicates_expand_tilde() {
+        let env = ShellEnv::new([("HOME", std::env::temp_dir().to_str().unwrap())]);
+        assert!(Predicate::DirExists("~".to_string()).check(&env));
+        assert!(Predicate::DirExists("${HOME}/.".to_string()).check(&env));
+        assert!(!Predicate::FileExists("${HOME".to_string()).check(&env));
+    }
+
+    #[test]
+    fn test_has_uses_simulated_path() {
+        let xml = r#"<condition><has>sh</has></condition>"#;
+        let condition: Condition = quick_xml::de::from_str(xml).unwrap();
+        assert!(!condition.check(&ShellEnv::new([("PATH", "/rush/no/such/dir")])).unwrap());
+        let mut env = ShellEnv::new([("PATH", "/rush/no/such/dir")]);
+        env.append_paths(["/bin", "/usr/bin"]).unwrap();
+        assert!(condition.check(&env).unwrap());
+    }
+
+    #[test]
+    fn test_port_open_is_proxy_only() {
+        let xml = r#"<condition><any><has>sh</has><not><port_open port="7890"/></not></any></condition>"#;
+        let condition: Condition = quick_xml::de::from_str(xml).unwrap();
+        assert!(matches!(condition.check(&ShellEnv::default()), Err(VisitorError::RuntimeCondition)));
+
+        let xml = r#"<raw><condition><port_open port="7890"/></condition>echo hi</raw>"#;
+        let raw: crate::core::script::raw::RawScript = quick_xml::de::from_str(xml).unwrap();
+        let mut context = crate::visitor::Visitor::default();
+        let error = crate::visitor::Visit::visit(&raw, &mut context, &mut Vec::new()).unwrap_err();
+        assert!(matches!(error, VisitorError::RuntimeCondition), "{error}");
+    }
+
+    #[test]
+    fn test_port_open_rejects_zero_timeout() {
+        let xml = r#"<condition><port_open port="7890" timeout_ms="0"/></condition>"#;
+        let error = quick_xml::de::from_str::<Condition>(xml).unwrap_err();
+        assert!(error.to_string().contains("timeout_ms must be greater than 0"), "{error}");
+    }
+}
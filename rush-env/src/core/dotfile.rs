@@ -0,0 +1,315 @@
+use crate::core::condition::Condition;
+use crate::shell_env::ShellEnv;
+use crate::visitor::VisitorError;
+use derive_more::{AsMut, AsRef, Deref, DerefMut};
+use include_dir::{Dir, include_dir};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// 随可执行文件一起发布的模板文件，`src` 在 rush 目录中找不到时从这里读取
+pub static TEMPLATE_DIR: Dir<'static> = include_dir!("$CARGO_MANIFEST_DIR/assets/template");
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Dotfile {
+    #[serde(rename = "@src")]
+    pub src: String,
+    #[serde(rename = "@dest")]
+    pub dest: String,
+    #[serde(rename = "@mode", default)]
+    pub mode: DotfileMode,
+    #[serde(default)]
+    pub condition: Condition,
+}
+
+#[derive(Default, Debug, Clone, Serialize)]
+#[derive(AsRef, AsMut, Deref, DerefMut)]
+pub struct Dotfiles(pub Vec<Dotfile>);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DotfileMode {
+    /// 在 dest 创建指向 src 的符号链接
+    #[default]
+    Link,
+    /// 原样复制 src 到 dest
+    Copy,
+    /// 展开 src 中的变量后写入 dest，规则见 [`ShellEnv::render`]；需要保留的 `$` 写作 `$$`
+    Render,
+}
+
+/// dest 与 src 的比对结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DotfileStatus {
+    Synced,
+    Missing,
+    Drifted,
+    SourceMissing,
+    Skipped,
+}
+
+enum Source {
+    File(PathBuf),
+    Embedded(&'static [u8]),
+}
+
+impl Dotfile {
+    pub fn tag() -> &'static str {
+        "<dotfile src dest mode>"
+    }
+
+    pub fn dest(&self, env: &ShellEnv) -> Result<PathBuf, VisitorError> {
+        Ok(PathBuf::from(env.expand(Self::tag(), &self.dest)?))
+    }
+
+    /// 相对路径的 src 先在 rush 目录中查找，再回退到内置模板；找到的文件转为绝对路径，
+    /// 保证符号链接不依赖当前目录
+    fn source(&self, rush_dir: &Path, env: &ShellEnv) -> Result<Option<Source>, VisitorError> {
+        let src = PathBuf::from(env.expand(Self::tag(), &self.src)?);
+        let file = rush_dir.join(&src);
+        if file.is_file() {
+            return Ok(Some(Source::File(std::path::absolute(file)?)));
+        }
+        Ok(TEMPLATE_DIR.get_file(&src).map(|file| Source::Embedded(file.contents())))
+    }
+
+    fn expected_content(&self, source: &Source, env: &ShellEnv) -> Result<Vec<u8>, VisitorError> {
+        let content = match source {
+            Source::File(file) => std::fs::read(file)?,
+            Source::Embedded(content) => content.to_vec(),
+        };
+        match self.mode {
+            DotfileMode::Render => Ok(env.render(Self::tag(), &String::from_utf8_lossy(&content))?.into_bytes()),
+            _ => Ok(content),
+        }
+    }
+
+    pub fn status(&self, rush_dir: impl AsRef<Path>, env: &ShellEnv) -> Result<DotfileStatus, VisitorError> {
+        if !self.condition.check(env)? {
+            return Ok(DotfileStatus::Skipped);
+        }
+        let Some(source) = self.source(rush_dir.as_ref(), env)? else {
+            return Ok(DotfileStatus::SourceMissing);
+        };
+        let dest = self.dest(env)?;
+        if dest.symlink_metadata().is_err() {
+            return Ok(DotfileStatus::Missing);
+        }
+        let synced = match (self.mode, &source) {
+            (DotfileMode::Link, Source::File(file)) => std::fs::read_link(&dest).is_ok_and(|target| &target == file),
+            (DotfileMode::Link, Source::Embedded(_)) => false,
+            _ => {
+                let expected = self.expected_content(&source, env)?;
+                !dest.is_symlink() && std::fs::read(&dest).is_ok_and(|content| content == expected)
+            }
+        };
+        Ok(if synced { DotfileStatus::Synced } else { DotfileStatus::Drifted })
+    }
+
+    /// 按 mode 放置 dest，已存在且不一致的 dest 先备份，返回备份路径
+    pub fn link(&self, rush_dir: impl AsRef<Path>, env: &ShellEnv) -> Result<Option<PathBuf>, VisitorError> {
+        let rush_dir = rush_dir.as_ref();
+        match self.status(rush_dir, env)? {
+            DotfileStatus::Synced | DotfileStatus::Skipped => return Ok(None),
+            DotfileStatus::SourceMissing => return Err(VisitorError::DotfileSourceNotExist(self.src.clone())),
+            DotfileStatus::Missing | DotfileStatus::Drifted => {}
+        }
+        let source = self
+            .source(rush_dir, env)?
+            .ok_or_else(|| VisitorError::DotfileSourceNotExist(self.src.clone()))?;
+        let dest = self.dest(env)?;
+        let backup = if dest.symlink_metadata().is_ok() {
+            let backup = Self::backup_path(&dest);
+            std::fs::rename(&dest, &backup)?;
+            Some(backup)
+        } else {
+            None
+        };
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        match (self.mode, &source) {
+            #[cfg(unix)]
+            (DotfileMode::Link, Source::File(file)) => std::os::unix::fs::symlink(file, &dest)?,
+            #[cfg(not(unix))]
+            (DotfileMode::Link, Source::File(_)) => return Err(VisitorError::LinkUnsupported(self.src.clone())),
+            (DotfileMode::Link, Source::Embedded(_)) => return Err(VisitorError::LinkEmbeddedDotfile(self.src.clone())),
+            _ => std::fs::write(&dest, self.expected_content(&source, env)?)?,
+        }
+        Ok(backup)
+    }
+
+    /// `dest.bak`，已被占用时依次尝试 `dest.bak.1`、`dest.bak.2`……
+    fn backup_path(dest: &Path) -> PathBuf {
+        let mut backup = PathBuf::from(format!("{}.bak", dest.display()));
+        let mut index = 1;
+        while backup.symlink_metadata().is_ok() {
+            backup = PathBuf::from(format!("{}.bak.{index}", dest.display()));
+            index += 1;
+        }
+        backup
+    }
+}
+
+impl Display for DotfileStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            DotfileStatus::Synced => "synced",
+            DotfileStatus::Missing => "missing",
+            DotfileStatus::Drifted => "drifted",
+            DotfileStatus::SourceMissing => "no-source",
+            DotfileStatus::Skipped => "skipped",
+        };
+        f.pad(status)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dotfiles {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct List {
+            #[serde(rename = "$value", default)]
+            element: Vec<Dotfile>,
+        }
+        Ok(Dotfiles(List::deserialize(deserializer)?.element))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rush-dotfile-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn dotfile(dir: &Path, mode: DotfileMode) -> Dotfile {
+        Dotfile {
+            src: "config.txt".to_string(),
+            dest: dir.join("home/.config.txt").to_str().unwrap().to_string(),
+            mode,
+            condition: Condition::default(),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_link_and_status() {
+        let env = ShellEnv::default();
+        let dir = work_dir("link");
+        std::fs::write(dir.join("config.txt"), "hello").unwrap();
+        let dotfile = dotfile(&dir, DotfileMode::Link);
+        assert_eq!(DotfileStatus::Missing, dotfile.status(&dir, &env).unwrap());
+        assert_eq!(None, dotfile.link(&dir, &env).unwrap());
+        assert_eq!(DotfileStatus::Synced, dotfile.status(&dir, &env).unwrap());
+        assert_eq!(dir.join("config.txt"), std::fs::read_link(dotfile.dest(&env).unwrap()).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_link_relative_rush_dir_is_absolute() {
+        let env = ShellEnv::default();
+        let dir = work_dir("relative");
+        std::fs::write(dir.join("config.txt"), "hello").unwrap();
+        let dotfile = dotfile(&dir, DotfileMode::Link);
+        let cwd = std::env::current_dir().unwrap();
+        let relative = std::iter::repeat_n("..", cwd.components().count())
+            .collect::<PathBuf>()
+            .join(dir.strip_prefix("/").unwrap());
+        dotfile.link(&relative, &env).unwrap();
+        let target = std::fs::read_link(dotfile.dest(&env).unwrap()).unwrap();
+        assert!(target.is_absolute(), "{}", target.display());
+        assert_eq!("hello", std::fs::read_to_string(dotfile.dest(&env).unwrap()).unwrap());
+        assert_eq!(DotfileStatus::Synced, dotfile.status(&relative, &env).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_copy_backs_up_drifted_dest() {
+        let env = ShellEnv::default();
+        let dir = work_dir("copy");
+        std::fs::write(dir.join("config.txt"), "hello").unwrap();
+        let dotfile = dotfile(&dir, DotfileMode::Copy);
+        std::fs::create_dir_all(dir.join("home")).unwrap();
+        std::fs::write(dotfile.dest(&env).unwrap(), "local edit").unwrap();
+        assert_eq!(DotfileStatus::Drifted, dotfile.status(&dir, &env).unwrap());
+        let backup = dotfile.link(&dir, &env).unwrap().unwrap();
+        assert_eq!("local edit", std::fs::read_to_string(backup).unwrap());
+        assert_eq!("hello", std::fs::read_to_string(dotfile.dest(&env).unwrap()).unwrap());
+        assert_eq!(DotfileStatus::Synced, dotfile.status(&dir, &env).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_render_expands_variables() {
+        let env = ShellEnv::default();
+        let dir = work_dir("render");
+        std::fs::write(dir.join("config.txt"), "path=${PATH}").unwrap();
+        let dotfile = dotfile(&dir, DotfileMode::Render);
+        dotfile.link(&dir, &env).unwrap();
+        let expected = format!("path={}", std::env::var("PATH").unwrap());
+        assert_eq!(expected, std::fs::read_to_string(dotfile.dest(&env).unwrap()).unwrap());
+        assert_eq!(DotfileStatus::Synced, dotfile.status(&dir, &env).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_render_follows_rush_var_rules() {
+        let env = ShellEnv::new([("HOME", "/home/me"), ("PASS", "pa$$word")]);
+        let dir = work_dir("render-rules");
+        std::fs::write(dir.join("config.txt"), "PATH=$$foo:~/bin:${HOME}/bin:$HOME ${EDITOR:-vim} ${PASS}").unwrap();
+        let dotfile = dotfile(&dir, DotfileMode::Render);
+        dotfile.link(&dir, &env).unwrap();
+        assert_eq!(
+            "PATH=$foo:~/bin:/home/me/bin:/home/me vim pa$$word",
+            std::fs::read_to_string(dotfile.dest(&env).unwrap()).unwrap()
+        );
+
+        std::fs::write(dir.join("config.txt"), "editor=${EDITOR}").unwrap();
+        let error = dotfile.status(&dir, &env).unwrap_err();
+        assert!(matches!(error, VisitorError::Expand { .. }), "{error}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_embedded_source() {
+        let env = ShellEnv::default();
+        let dir = work_dir("embedded");
+        let dotfile = Dotfile {
+            src: "tmux.conf".to_string(),
+            ..dotfile(&dir, DotfileMode::Copy)
+        };
+        dotfile.link(&dir, &env).unwrap();
+        assert_eq!(
+            TEMPLATE_DIR.get_file("tmux.conf").unwrap().contents(),
+            std::fs::read(dotfile.dest(&env).unwrap()).unwrap()
+        );
+        let link = Dotfile {
+            mode: DotfileMode::Link,
+            dest: dir.join("home/.tmux.conf").to_str().unwrap().to_string(),
+            ..dotfile
+        };
+        assert!(matches!(link.link(&dir, &env), Err(VisitorError::LinkEmbeddedDotfile(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_dest_reports_span() {
+        let env = ShellEnv::default();
+        let dotfile = Dotfile {
+            dest: "${HOME/.config".to_string(),
+            ..dotfile(Path::new("/tmp"), DotfileMode::Copy)
+        };
+        let error = dotfile.dest(&env).unwrap_err();
+        assert!(matches!(error, VisitorError::Expand { .. }));
+        assert!(error.to_string().ends_with("${HOME/.config\n^^^^^^^^^^^^^^"));
+    }
+}
@@ -2,7 +2,6 @@ use crate::visitor::{Visit, Visitor, VisitorError};
 use derive_more::{AsMut, AsRef, Deref, DerefMut};
 use serde::{Deserialize, Serialize};
 use std::ffi::OsStr;
-use std::path::PathBuf;
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -17,37 +16,11 @@ impl Path {
     pub fn tag() -> &'static str {
         "<path>"
     }
-
-    fn export(&self) -> Result<(), VisitorError> {
-        if let Some(path) = std::env::var_os("PATH") {
-            let mut paths = std::env::split_paths(&path).collect::<Vec<_>>();
-            paths.push(PathBuf::from(&self.0));
-            let new_path = std::env::join_paths(paths)?;
-            unsafe {
-                std::env::set_var("PATH", &new_path);
-            }
-        }
-        Ok(())
-    }
-}
-
-impl Paths {
-    fn export(&self) -> Result<(), VisitorError> {
-        if let Some(path) = std::env::var_os("PATH") {
-            let mut paths = std::env::split_paths(&path).collect::<Vec<_>>();
-            paths.extend(self.0.iter().map(PathBuf::from));
-            let new_path = std::env::join_paths(paths)?;
-            unsafe {
-                std::env::set_var("PATH", &new_path);
-            }
-        }
-        Ok(())
-    }
 }
 
 impl Visit for Path {
     fn visit<'a>(&'a self, context: &mut Visitor<'a>, _writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        self.export()?;
+        context.env.append_paths([self])?;
         context.paths.push(self);
         Ok(())
     }
@@ -55,7 +28,7 @@ impl Visit for Path {
 
 impl Visit for Paths {
     fn visit<'a>(&'a self, context: &mut Visitor<'a>, _writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        self.export()?;
+        context.env.append_paths(self.0.iter())?;
         context.paths.extend(self.0.iter());
         Ok(())
     }
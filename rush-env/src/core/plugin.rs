@@ -29,12 +29,12 @@ impl Plugin {
 
 impl Visit for Plugin {
     fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        if !self.condition.check() {
+        if !self.condition.check(&context.env)? {
             return Ok(());
         }
         let name = format!("{}_DIR", self.name.to_uppercase());
         let value = self.work_dir.clone();
-        ExportScript::export(name, value, writer)?;
+        ExportScript::export(&mut context.env, name, value, writer)?;
         self.scripts.visit(context, writer)?;
         Ok(())
     }
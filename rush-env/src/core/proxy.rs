@@ -1,15 +1,532 @@
+use crate::config::proxy_config::{ProxyConfig, ProxyTool};
+use crate::core::condition::{Condition, PortOpen, Predicate};
 use crate::core::script::Scripts;
+use crate::core::script::function::{FunctionScript, re_indent};
+use crate::shell_env::ShellEnv;
 use crate::visitor::{Visit, Visitor, VisitorError};
-use serde::{Deserialize, Serialize};
+use rush_var::serde::Template;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+use std::str::FromStr;
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+/// 读写均为扁平的 `<proxy host http_port socks_port enabled>` 元素
+#[derive(Default, Debug, Clone)]
 pub struct Proxy {
+    /// `host`、`http_port`、`socks_port` 的原始文本，序列化时原样写回，`config` 中是展开后的值
+    pub host: Template,
+    pub http_port: Template,
+    pub socks_port: Template,
+    pub config: ProxyConfig,
+    pub enabled: bool,
+    pub condition: Condition,
     pub scripts: Scripts,
 }
 
+impl Proxy {
+    pub fn tag() -> &'static str {
+        "<proxy host http_port socks_port>"
+    }
+
+    /// 已配置且当前环境中存在的工具
+    fn available_tools(&self, env: &ShellEnv) -> Vec<ProxyTool> {
+        self.config
+            .tools
+            .iter()
+            .filter(|tool| Predicate::Has(tool.command().to_string()).check(env))
+            .copied()
+            .collect()
+    }
+
+    fn variables(&self, tools: &[ProxyTool]) -> Vec<(String, String)> {
+        let mut variables = self.config.variables();
+        variables.extend(tools.iter().flat_map(|tool| tool.variables(&self.config)));
+        variables
+    }
+
+    /// 以 `NAME="value" \` 的形式为单条命令附加代理变量
+    fn proxied_command(variables: &[(String, String)], command: &str) -> String {
+        variables
+            .iter()
+            .map(|(name, value)| format!(r#"{name}="{value}" \"#))
+            .chain(std::iter::once(command.to_string()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 需要通过 alias 附加参数的工具；已有同名包装函数的工具由包装函数负责，避免参数重复
+    fn aliased_tools<'t>(&self, tools: &'t [ProxyTool]) -> impl Iterator<Item = &'t ProxyTool> {
+        tools
+            .iter()
+            .filter(|tool| !self.config.wraps.iter().any(|wrap| wrap == tool.command()))
+    }
+
+    fn write_pr(&self, tools: &[ProxyTool], variables: &[(String, String)], writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        let mut lines = vec![];
+        for tool in tools {
+            if let Some(arguments) = tool.arguments(&self.config) {
+                let command = tool.command();
+                lines.push(format!(r#"if [[ "$1" == "{command}" ]]; then"#));
+                lines.push("    shift".to_string());
+                lines.push(format!(r#"    set -- {command} {arguments} "$@""#));
+                lines.push("fi".to_string());
+            }
+        }
+        lines.push(Self::proxied_command(variables, r#""$@""#));
+        FunctionScript::define("pr", lines.join("\n"), writer)
+    }
+
+    fn write_proxy_on(
+        &self,
+        tools: &[ProxyTool],
+        variables: &[(String, String)],
+        writer: &mut impl std::io::Write,
+    ) -> Result<(), VisitorError> {
+        let mut lines = variables
+            .iter()
+            .map(|(name, value)| format!(r#"export {name}="{value}""#))
+            .collect::<Vec<_>>();
+        for tool in self.aliased_tools(tools) {
+            if let Some(arguments) = tool.arguments(&self.config) {
+                let command = tool.command();
+                lines.push(format!("alias {command}='{command} {arguments}'"));
+            }
+        }
+        FunctionScript::define("proxy_on", lines.join("\n"), writer)
+    }
+
+    fn write_proxy_off(
+        &self,
+        tools: &[ProxyTool],
+        variables: &[(String, String)],
+        writer: &mut impl std::io::Write,
+    ) -> Result<(), VisitorError> {
+        let names = variables.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(" ");
+        let mut lines = vec![format!("unset {names}")];
+        for tool in self.aliased_tools(tools) {
+            if tool.arguments(&self.config).is_some() {
+                lines.push(format!("unalias {} 2>/dev/null", tool.command()));
+            }
+        }
+        FunctionScript::define("proxy_off", lines.join("\n"), writer)
+    }
+
+    fn write_proxy_status(&self, variables: &[(String, String)], writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        let mut lines = vec![
+            r#"if [[ -n "${HTTP_PROXY}" ]]; then"#.to_string(),
+            r#"    echo "proxy: on""#.to_string(),
+            "else".to_string(),
+            r#"    echo "proxy: off""#.to_string(),
+            "fi".to_string(),
+        ];
+        lines.extend(variables.iter().map(|(name, _)| format!(r#"echo "{name}=${{{name}}}""#)));
+        FunctionScript::define("proxy_status", lines.join("\n"), writer)
+    }
+
+    /// 为配置的命令生成同名包装函数：代理开启（`HTTP_PROXY` 非空）时附加代理变量与参数，关闭时直接执行；命令不存在时跳过
+    fn write_wraps(
+        &self,
+        env: &ShellEnv,
+        tools: &[ProxyTool],
+        variables: &[(String, String)],
+        writer: &mut impl std::io::Write,
+    ) -> Result<(), VisitorError> {
+        for wrap in &self.config.wraps {
+            if !Predicate::Has(wrap.clone()).check(env) {
+                continue;
+            }
+            let arguments = tools
+                .iter()
+                .filter(|tool| tool.command() == wrap)
+                .find_map(|tool| tool.arguments(&self.config));
+            let command = match arguments {
+                Some(arguments) => format!(r#"command {wrap} {arguments} "$@""#),
+                None => format!(r#"command {wrap} "$@""#),
+            };
+            let body = [
+                r#"if [[ -n "${HTTP_PROXY}" ]]; then"#.to_string(),
+                re_indent(&Self::proxied_command(variables, &command), "    "),
+                "else".to_string(),
+                format!(r#"    command {wrap} "$@""#),
+                "fi".to_string(),
+            ];
+            FunctionScript::define(wrap, body.join("\n"), writer)?;
+        }
+        Ok(())
+    }
+}
+
 impl Visit for Proxy {
+    /// 开关函数总是生成；`<condition>` 只决定是否自动执行 `proxy_on`，其中的 `<port_open>` 在 shell 启动时探测，
+    /// 这样生成脚本时代理不可用、之后才切换到可用网络的情况也能自动开启
     fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        let tools = self.available_tools(&context.env);
+        let variables = self.variables(&tools);
+        self.write_pr(&tools, &variables, writer)?;
+        self.write_proxy_on(&tools, &variables, writer)?;
+        self.write_proxy_off(&tools, &variables, writer)?;
+        self.write_proxy_status(&variables, writer)?;
+        self.write_wraps(&context.env, &tools, &variables, writer)?;
+        if self.enabled {
+            match self.condition.shell_test(&context.env).as_str() {
+                "true" => writeln!(writer, "proxy_on")?,
+                "false" => {}
+                test => {
+                    FunctionScript::define(PortOpen::PROBE, PortOpen::PROBE_BODY, writer)?;
+                    writeln!(writer, "if {test}; then\n    proxy_on\nfi")?;
+                }
+            }
+        }
         self.scripts.visit(context, writer)
     }
 }
+
+impl<'de> Deserialize<'de> for Proxy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Tool {
+            #[serde(rename = "$text")]
+            tool: ProxyTool,
+        }
+        #[derive(Deserialize)]
+        struct Element {
+            #[serde(rename = "@host")]
+            host: Template,
+            #[serde(rename = "@http_port")]
+            http_port: Template,
+            #[serde(rename = "@socks_port")]
+            socks_port: Template,
+            #[serde(rename = "@enabled", default)]
+            enabled: bool,
+            #[serde(rename = "no_proxy", default)]
+            no_proxy: Vec<String>,
+            #[serde(rename = "tool", default)]
+            tools: Vec<Tool>,
+            #[serde(rename = "wrap", default)]
+            wraps: Vec<String>,
+            #[serde(default)]
+            condition: Condition,
+            #[serde(default)]
+            scripts: Scripts,
+        }
+        /// 用 [`rush_var::serde::with_env`] 设置的变量源展开后解析
+        fn expand<T: FromStr<Err: Display>, E: serde::de::Error>(template: &Template) -> Result<T, E> {
+            let expanded = template
+                .resolve()
+                .map_err(|error| E::custom(format!("cannot expand {:?}: {error}", template.as_str())))?;
+            expanded.parse().map_err(|error| {
+                E::custom(format!(
+                    "invalid value {expanded:?} (expanded from {:?}): {error}",
+                    template.as_str()
+                ))
+            })
+        }
+        let element = Element::deserialize(deserializer)?;
+        let config = ProxyConfig::new(expand(&element.host)?, expand(&element.http_port)?, expand(&element.socks_port)?);
+        Ok(Proxy {
+            host: element.host,
+            http_port: element.http_port,
+            socks_port: element.socks_port,
+            config: config
+                .with_no_proxy(element.no_proxy)
+                .with_tools(element.tools.into_iter().map(|tool| tool.tool).collect())
+                .with_wraps(element.wraps),
+            enabled: element.enabled,
+            condition: element.condition,
+            scripts: element.scripts,
+        })
+    }
+}
+
+impl Serialize for Proxy {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Tool<'a> {
+            #[serde(rename = "$text")]
+            tool: &'a ProxyTool,
+        }
+        #[derive(Serialize)]
+        struct Element<'a> {
+            #[serde(rename = "@host")]
+            host: &'a Template,
+            #[serde(rename = "@http_port")]
+            http_port: &'a Template,
+            #[serde(rename = "@socks_port")]
+            socks_port: &'a Template,
+            #[serde(rename = "@enabled")]
+            enabled: bool,
+            no_proxy: &'a [String],
+            #[serde(rename = "tool")]
+            tools: Vec<Tool<'a>>,
+            #[serde(rename = "wrap")]
+            wraps: &'a [String],
+            #[serde(skip_serializing_if = "is_always")]
+            condition: &'a Condition,
+            #[serde(skip_serializing_if = "is_empty")]
+            scripts: &'a Scripts,
+        }
+        fn is_always(condition: &&Condition) -> bool {
+            matches!(***condition, Predicate::None)
+        }
+        fn is_empty(scripts: &&Scripts) -> bool {
+            scripts.is_empty()
+        }
+        Element {
+            host: &self.host,
+            http_port: &self.http_port,
+            socks_port: &self.socks_port,
+            enabled: self.enabled,
+            no_proxy: &self.config.no_proxy,
+            tools: self.config.tools.iter().map(|tool| Tool { tool }).collect(),
+            wraps: &self.config.wraps,
+            condition: &self.condition,
+            scripts: &self.scripts,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"
+        <proxy host="127.0.0.1" http_port="7890" socks_port="7891">
+            <no_proxy>localhost</no_proxy>
+            <no_proxy>127.0.0.1</no_proxy>
+        </proxy>
+    "#;
+
+    #[test]
+    fn test_deserialize_proxy_config() {
+        let proxy: Proxy = quick_xml::de::from_str(XML).unwrap();
+        assert_eq!(proxy.config.http_proxy, "http://127.0.0.1:7890");
+        assert_eq!(proxy.config.https_proxy, "http://127.0.0.1:7890");
+        assert_eq!(proxy.config.all_proxy, "socks5://127.0.0.1:7891");
+        assert_eq!(proxy.config.no_proxy, ["localhost", "127.0.0.1"]);
+        assert!(!proxy.enabled);
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let xml = r#"
+            <proxy host="127.0.0.1" http_port="7890" socks_port="7891" enabled="true">
+                <no_proxy>localhost</no_proxy>
+                <tool>git</tool>
+                <tool>npm</tool>
+                <wrap>git</wrap>
+            </proxy>
+        "#;
+        let proxy: Proxy = quick_xml::de::from_str(xml).unwrap();
+        let serialized = quick_xml::se::to_string_with_root("proxy", &proxy).unwrap();
+        assert_eq!(
+            r#"<proxy host="127.0.0.1" http_port="7890" socks_port="7891" enabled="true"><no_proxy>localhost</no_proxy><tool>git</tool><tool>npm</tool><wrap>git</wrap></proxy>"#,
+            serialized
+        );
+        let parsed: Proxy = quick_xml::de::from_str(&serialized).unwrap();
+        assert_eq!(parsed.config.http_proxy, "http://127.0.0.1:7890");
+        assert_eq!(parsed.config.no_proxy, ["localhost"]);
+        assert_eq!(parsed.config.tools, [ProxyTool::Git, ProxyTool::Npm]);
+        assert_eq!(parsed.config.wraps, ["git"]);
+        assert!(parsed.enabled);
+        assert_eq!(serialized, quick_xml::se::to_string_with_root("proxy", &parsed).unwrap());
+    }
+
+    #[test]
+    fn test_deserialize_expands_attributes() {
+        let xml = r#"<proxy host="${PROXY_HOST:-127.0.0.1}" http_port="$PROXY_PORT" socks_port="${PROXY_PORT}1"/>"#;
+        let proxy: Proxy = rush_var::serde::with_env([("PROXY_PORT", "789")], || quick_xml::de::from_str(xml)).unwrap();
+        assert_eq!(proxy.config.http_proxy, "http://127.0.0.1:789");
+        assert_eq!(proxy.config.all_proxy, "socks5://127.0.0.1:7891");
+        assert_eq!(
+            r#"<proxy host="${PROXY_HOST:-127.0.0.1}" http_port="$PROXY_PORT" socks_port="${PROXY_PORT}1" enabled="false"/>"#,
+            quick_xml::se::to_string_with_root("proxy", &proxy).unwrap()
+        );
+        let invalid = rush_var::serde::with_env([("PROXY_PORT", "auto")], || quick_xml::de::from_str::<Proxy>(xml));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_visit_proxy() {
+        let proxy: Proxy = quick_xml::de::from_str(XML).unwrap();
+        let mut buf = Vec::new();
+        proxy.visit(&mut Visitor::default(), &mut buf).unwrap();
+        #[rustfmt::skip]
+        let expected =
+r#"function pr {
+    HTTP_PROXY="http://127.0.0.1:7890" \
+    http_proxy="http://127.0.0.1:7890" \
+    HTTPS_PROXY="http://127.0.0.1:7890" \
+    https_proxy="http://127.0.0.1:7890" \
+    ALL_PROXY="socks5://127.0.0.1:7891" \
+    all_proxy="socks5://127.0.0.1:7891" \
+    NO_PROXY="localhost,127.0.0.1" \
+    no_proxy="localhost,127.0.0.1" \
+    "$@"
+}
+function proxy_on {
+    export HTTP_PROXY="http://127.0.0.1:7890"
+    export http_proxy="http://127.0.0.1:7890"
+    export HTTPS_PROXY="http://127.0.0.1:7890"
+    export https_proxy="http://127.0.0.1:7890"
+    export ALL_PROXY="socks5://127.0.0.1:7891"
+    export all_proxy="socks5://127.0.0.1:7891"
+    export NO_PROXY="localhost,127.0.0.1"
+    export no_proxy="localhost,127.0.0.1"
+}
+function proxy_off {
+    unset HTTP_PROXY http_proxy HTTPS_PROXY https_proxy ALL_PROXY all_proxy NO_PROXY no_proxy
+}
+function proxy_status {
+    if [[ -n "${HTTP_PROXY}" ]]; then
+        echo "proxy: on"
+    else
+        echo "proxy: off"
+    fi
+    echo "HTTP_PROXY=${HTTP_PROXY}"
+    echo "http_proxy=${http_proxy}"
+    echo "HTTPS_PROXY=${HTTPS_PROXY}"
+    echo "https_proxy=${https_proxy}"
+    echo "ALL_PROXY=${ALL_PROXY}"
+    echo "all_proxy=${all_proxy}"
+    echo "NO_PROXY=${NO_PROXY}"
+    echo "no_proxy=${no_proxy}"
+}
+"#;
+        assert_eq!(expected, String::from_utf8(buf).unwrap());
+    }
+
+    #[test]
+    fn test_condition_gates_only_auto_proxy_on() {
+        let visit = |condition: &str| {
+            let xml = format!(r#"<proxy host="127.0.0.1" http_port="7890" socks_port="7891" enabled="true">{condition}</proxy>"#);
+            let proxy: Proxy = quick_xml::de::from_str(&xml).unwrap();
+            let mut buf = Vec::new();
+            proxy.visit(&mut Visitor::default(), &mut buf).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        let output = visit(r#"<condition><port_open port="7890" timeout_ms="50"/></condition>"#);
+        for function in ["pr", "proxy_on", "proxy_off", "proxy_status"] {
+            assert!(output.contains(&format!("function {function} {{")), "{output}");
+        }
+        #[rustfmt::skip]
+        let expected =
+r#"function rush_port_open {
+    setopt local_options no_monitor no_notify
+    zmodload zsh/net/tcp zsh/zselect 2>/dev/null || return 1
+    { ztcp "$1" "$2" && ztcp -c "$REPLY"; } 2>/dev/null &
+    local pid=$! waited=0
+    while kill -0 $pid 2>/dev/null; do
+        if (( waited >= $3 )); then
+            kill $pid 2>/dev/null
+            return 1
+        fi
+        zselect -t 1
+        (( waited++ ))
+    done
+    wait $pid
+}
+if rush_port_open 127.0.0.1 7890 5; then
+    proxy_on
+fi
+"#;
+        assert!(output.ends_with(expected), "{output}");
+
+        // 生成时即可确定的条件不需要运行时探测
+        let output = visit("<condition><not><dir_exists>/rush/no/such/dir</dir_exists></not></condition>");
+        assert!(
+            output.contains("function proxy_on {") && output.ends_with("}\nproxy_on\n"),
+            "{output}"
+        );
+        let output = visit("<condition><dir_exists>/rush/no/such/dir</dir_exists></condition>");
+        assert!(
+            output.contains("function proxy_on {") && !output.contains("rush_port_open") && output.ends_with("}\n"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_tool_settings_and_wraps() {
+        let xml = r#"
+            <proxy host="127.0.0.1" http_port="7890" socks_port="7891">
+                <tool>git</tool>
+                <tool>cargo</tool>
+                <wrap>git</wrap>
+            </proxy>
+        "#;
+        let proxy: Proxy = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(proxy.config.tools, [ProxyTool::Git, ProxyTool::Cargo]);
+        assert_eq!(proxy.config.wraps, ["git"]);
+
+        let tools = [ProxyTool::Git, ProxyTool::Cargo];
+        let variables = proxy.variables(&tools);
+        assert_eq!(
+            variables.last().unwrap(),
+            &("CARGO_HTTP_PROXY".to_string(), "http://127.0.0.1:7890".to_string())
+        );
+
+        let mut buf = Vec::new();
+        proxy.write_pr(&tools, &variables[..1], &mut buf).unwrap();
+        #[rustfmt::skip]
+        let expected =
+r#"function pr {
+    if [[ "$1" == "git" ]]; then
+        shift
+        set -- git -c http.proxy="http://127.0.0.1:7890" "$@"
+    fi
+    HTTP_PROXY="http://127.0.0.1:7890" \
+    "$@"
+}
+"#;
+        assert_eq!(expected, String::from_utf8(buf).unwrap());
+
+        let mut buf = Vec::new();
+        proxy.write_proxy_off(&tools, &variables[..1], &mut buf).unwrap();
+        #[rustfmt::skip]
+        let expected =
+r#"function proxy_off {
+    unset HTTP_PROXY
+}
+"#;
+        assert_eq!(expected, String::from_utf8(buf).unwrap());
+
+        // git 已有包装函数，proxy_on 不再定义会重复附加参数的 alias
+        let mut buf = Vec::new();
+        proxy.write_proxy_on(&tools, &variables[..1], &mut buf).unwrap();
+        assert_eq!(
+            "function proxy_on {\n    export HTTP_PROXY=\"http://127.0.0.1:7890\"\n}\n",
+            String::from_utf8(buf).unwrap()
+        );
+
+        let dir = std::env::temp_dir().join(format!("rush-proxy-wrap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("git"), "").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(dir.join("git"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let env = ShellEnv::new([("PATH", dir.to_str().unwrap())]);
+        let mut buf = Vec::new();
+        proxy.write_wraps(&env, &tools, &variables[..1], &mut buf).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        #[rustfmt::skip]
+        let expected =
+r#"function git {
+    if [[ -n "${HTTP_PROXY}" ]]; then
+        HTTP_PROXY="http://127.0.0.1:7890" \
+        command git -c http.proxy="http://127.0.0.1:7890" "$@"
+    else
+        command git "$@"
+    fi
+}
+"#;
+        assert_eq!(expected, String::from_utf8(buf).unwrap());
+    }
+}
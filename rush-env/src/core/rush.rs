@@ -1,3 +1,5 @@
+use crate::config::antidote_config::Antidote;
+use crate::core::dotfile::Dotfiles;
 use crate::core::language::Languages;
 use crate::core::plugin::Plugins;
 use crate::core::proxy::Proxy;
@@ -9,7 +11,10 @@ use serde::{Deserialize, Serialize};
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Rush {
-    pub proxy: Proxy,
+    #[serde(default)]
+    pub proxy: Option<Proxy>,
+    #[serde(default)]
+    pub antidote: Option<Antidote>,
     #[serde(default)]
     pub plugins: Plugins,
     #[serde(default)]
@@ -22,15 +27,22 @@ pub struct Rush {
     pub languages: Languages,
     #[serde(default)]
     pub tools: Tools,
+    #[serde(default)]
+    pub dotfiles: Dotfiles,
 }
 
 impl Visit for Rush {
     fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
         context.section.say(writer, "🌐 Proxy Section 🌐")?;
-        self.proxy.visit(context, writer)?;
+        if let Some(proxy) = &self.proxy {
+            proxy.visit(context, writer)?;
+        }
         writeln!(writer)?;
 
         context.section.say(writer, "🚀 Plugins Section 🚀")?;
+        if let Some(antidote) = &self.antidote {
+            antidote.visit(context, writer)?;
+        }
         self.plugins.visit(context, writer)?;
         writeln!(writer)?;
 
@@ -1,4 +1,5 @@
 use crate::core::script::alias::AliasScript;
+use crate::core::script::envfile::EnvFileScript;
 use crate::core::script::eval::EvalScript;
 use crate::core::script::export::ExportScript;
 use crate::core::script::function::FunctionScript;
@@ -11,6 +12,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 use tracing::warn;
 
 pub mod alias;
+pub mod envfile;
 pub mod eval;
 pub mod export;
 pub mod function;
@@ -23,6 +25,8 @@ pub mod var;
 pub enum Script {
     #[serde(rename = "alias")]
     Alias(AliasScript),
+    #[serde(rename = "envfile")]
+    EnvFile(EnvFileScript),
     #[serde(rename = "eval")]
     Eval(EvalScript),
     #[serde(rename = "export")]
@@ -61,6 +65,14 @@ impl Visit for Script {
     fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
         match self {
             Script::Alias(alias) => alias.visit(context, writer),
+            Script::EnvFile(envfile) => match envfile.visit(context, writer) {
+                Ok(()) => Ok(()),
+                Err(VisitorError::EnvFileNotExist(file)) => {
+                    warn!("Env file {} does not exist", file);
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            },
             Script::Eval(eval) => eval.visit(context, writer),
             Script::Export(export) => export.visit(context, writer),
             Script::Function(function) => function.visit(context, writer),
@@ -20,8 +20,8 @@ impl AliasScript {
 }
 
 impl Visit for AliasScript {
-    fn visit<'a>(&'a self, _context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        if !self.condition.check() {
+    fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        if !self.condition.check(&context.env)? {
             return Ok(());
         }
         writeln!(writer, r#"alias {} = "{}""#, self.name, self.command)?;
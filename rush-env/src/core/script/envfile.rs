@@ -0,0 +1,96 @@
+use crate::core::condition::Condition;
+use crate::shell_env::ShellEnv;
+use crate::visitor::{Visit, Visitor, VisitorError};
+use rush_var::dotenv::Dotenv;
+use rush_var::env_source::FnEnvSource;
+use serde::{Deserialize, Serialize};
+
+/// 加载 dotenv 文件，把其中的变量写入脚本；`export="true"` 时以 `export` 导出
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct EnvFileScript {
+    #[serde(rename = "@path")]
+    pub path: String,
+    #[serde(rename = "@export", default)]
+    pub export: bool,
+    #[serde(default)]
+    pub condition: Condition,
+}
+
+impl EnvFileScript {
+    pub fn tag() -> &'static str {
+        "<envfile path export>"
+    }
+
+    /// 值已在加载时展开，以单引号原样写入，并以字面值记录到模拟环境中
+    fn define(env: &mut ShellEnv, export: bool, name: &str, value: &str, buf: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        if name.to_uppercase() == "PATH" {
+            return Err(VisitorError::ExportPath(value.to_string()));
+        }
+        let quoted = value.replace('\'', r"'\''");
+        if export {
+            writeln!(buf, "export {name}='{quoted}'")?;
+        } else {
+            writeln!(buf, "{name}='{quoted}'")?;
+        }
+        env.set(name, value.replace('$', "$$"));
+        Ok(())
+    }
+}
+
+impl Visit for EnvFileScript {
+    fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        if !self.condition.check(&context.env)? {
+            return Ok(());
+        }
+        let file = context.env.expand_path(Self::tag(), &self.path)?;
+        if !file.is_file() {
+            return Err(VisitorError::EnvFileNotExist(self.path.clone()));
+        }
+        let env = &context.env;
+        let dotenv =
+            Dotenv::from_path_with(&file, &FnEnvSource(|name: &str| env.resolve(name))).map_err(|source| VisitorError::Dotenv {
+                path: self.path.clone(),
+                source: Box::new(source),
+            })?;
+        for (name, value) in dotenv.iter() {
+            Self::define(&mut context.env, self.export, name, value, writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_envfile() {
+        let file = std::env::temp_dir().join(format!("rush-envfile-{}.env", std::process::id()));
+        std::fs::write(&file, "export GOBIN=${GOPATH}/bin\nTOKEN=\"it's \\$secret\"\n").unwrap();
+        let xml = format!(r#"<envfile path="{}" export="true"/>"#, file.display());
+        let envfile: EnvFileScript = quick_xml::de::from_str(&xml).unwrap();
+        let missing = EnvFileScript {
+            path: "/rush/no/such/.env".to_string(),
+            ..envfile.clone()
+        };
+        let mut context = Visitor {
+            env: ShellEnv::new([("HOME", "/home/me")]),
+            ..Default::default()
+        };
+        context.env.set("GOPATH", "$HOME/go");
+        let mut buf = Vec::new();
+        envfile.visit(&mut context, &mut buf).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(
+            "export GOBIN='/home/me/go/bin'\nexport TOKEN='it'\\''s $secret'\n",
+            String::from_utf8(buf).unwrap()
+        );
+        assert_eq!("it's $secret!", context.env.expand("<test>", "${TOKEN}!").unwrap());
+
+        assert!(matches!(
+            missing.visit(&mut context, &mut Vec::new()),
+            Err(VisitorError::EnvFileNotExist(_))
+        ));
+    }
+}
@@ -18,8 +18,8 @@ impl EvalScript {
 }
 
 impl Visit for EvalScript {
-    fn visit<'a>(&'a self, _context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        if !self.condition.check() {
+    fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        if !self.condition.check(&context.env)? {
             return Ok(());
         }
         writeln!(writer, r#"eval $({})"#, self.script)?;
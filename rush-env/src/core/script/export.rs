@@ -1,4 +1,5 @@
 use crate::core::condition::Condition;
+use crate::shell_env::ShellEnv;
 use crate::visitor::{Visit, Visitor, VisitorError};
 use serde::{Deserialize, Serialize};
 
@@ -9,42 +10,111 @@ pub struct ExportScript {
     pub name: String,
     #[serde(rename = "$text")]
     pub value: String,
+    #[serde(rename = "@eval", default)]
+    pub eval: Eval,
     #[serde(default)]
     pub condition: Condition,
 }
 
+/// `$(...)` 命令替换的求值时机
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Eval {
+    /// 原样写入脚本，由 shell 在启动时执行
+    #[default]
+    Shell,
+    /// 生成脚本时立即执行，写入执行结果
+    Now,
+}
+
 impl ExportScript {
     pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             value: value.into(),
+            eval: Eval::default(),
             condition: Condition::default(),
         }
     }
 
     pub fn tag() -> &'static str {
-        "<export name>"
+        "<export name eval>"
     }
 
-    pub fn export(name: impl AsRef<str>, value: impl AsRef<str>, buf: &mut impl std::io::Write) -> Result<(), VisitorError> {
+    /// 写入 `export NAME="value"`，同时记录到模拟环境 `env` 中，供之后的展开与条件判断读取
+    pub fn export(
+        env: &mut ShellEnv,
+        name: impl AsRef<str>,
+        value: impl AsRef<str>,
+        buf: &mut impl std::io::Write,
+    ) -> Result<(), VisitorError> {
         let name = name.as_ref();
         let value = value.as_ref();
         if name.to_uppercase() == "PATH" {
             return Err(VisitorError::ExportPath(value.to_string()));
         }
-        unsafe {
-            std::env::set_var(name, value);
-        }
+        env.set(name, value);
         writeln!(buf, r#"export {name}="{value}""#)?;
         Ok(())
     }
 }
 
 impl Visit for ExportScript {
-    fn visit<'a>(&'a self, _context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        if !self.condition.check() {
+    fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        if !self.condition.check(&context.env)? {
             return Ok(());
         }
-        Self::export(&self.name, &self.value, writer)
+        match self.eval {
+            Eval::Shell => Self::export(&mut context.env, &self.name, &self.value, writer),
+            Eval::Now => {
+                let value = context.env.eval(Self::tag(), &self.value)?;
+                Self::export(&mut context.env, &self.name, value, writer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_now() {
+        let xml = r#"<export name="RUSH_TEST_EVAL" eval="now">$(printf '%s' "${RUSH_TEST_EVAL_INPUT:-rush}")/bin</export>"#;
+        let export: ExportScript = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(Eval::Now, export.eval);
+        let mut buf = Vec::new();
+        export.visit(&mut Visitor::default(), &mut buf).unwrap();
+        assert_eq!("export RUSH_TEST_EVAL=\"rush/bin\"\n", String::from_utf8(buf).unwrap());
+
+        let shell = ExportScript {
+            eval: Eval::Shell,
+            ..export
+        };
+        let mut buf = Vec::new();
+        shell.visit(&mut Visitor::default(), &mut buf).unwrap();
+        assert!(String::from_utf8(buf).unwrap().contains("$(printf"));
+    }
+
+    #[test]
+    fn test_export_updates_simulated_env() {
+        let export = ExportScript::new("RUSH_TEST_GOPATH", "$HOME/go");
+        let now = ExportScript {
+            eval: Eval::Now,
+            ..ExportScript::new("RUSH_TEST_GOBIN", "${RUSH_TEST_GOPATH}/bin")
+        };
+        let mut context = Visitor {
+            env: ShellEnv::new([("HOME", "/home/me")]),
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        export.visit(&mut context, &mut buf).unwrap();
+        now.visit(&mut context, &mut buf).unwrap();
+        assert!(
+            String::from_utf8(buf)
+                .unwrap()
+                .ends_with("export RUSH_TEST_GOBIN=\"/home/me/go/bin\"\n")
+        );
+        assert!(std::env::var("RUSH_TEST_GOPATH").is_err());
     }
 }
@@ -17,19 +17,22 @@ impl FunctionScript {
     pub fn tag() -> &'static str {
         "<function name>"
     }
+
+    pub fn define(name: impl AsRef<str>, body: impl AsRef<str>, buf: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        writeln!(buf, "function {} {{", name.as_ref())?;
+        let body = re_indent(body.as_ref(), "    ");
+        writeln!(buf, "{body}")?;
+        writeln!(buf, "}}")?;
+        Ok(())
+    }
 }
 
 impl Visit for FunctionScript {
-    fn visit<'a>(&'a self, _context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        if !self.condition.check() {
+    fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        if !self.condition.check(&context.env)? {
             return Ok(());
         }
-        writeln!(writer, "function {} {{", self.name)?;
-        // let lines = self.body.trim_start_matches('\n').trim_end();
-        let body = re_indent(&self.body, "    ");
-        writeln!(writer, "{body}")?;
-        writeln!(writer, "}}")?;
-        Ok(())
+        Self::define(&self.name, &self.body, writer)
     }
 }
 
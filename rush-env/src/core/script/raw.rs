@@ -18,8 +18,8 @@ impl RawScript {
 }
 
 impl Visit for RawScript {
-    fn visit<'a>(&'a self, _context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        if !self.condition.check() {
+    fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        if !self.condition.check(&context.env)? {
             return Ok(());
         }
         writeln!(writer, "{}", self.script)?;
@@ -1,8 +1,6 @@
 use crate::core::condition::Condition;
 use crate::visitor::{Visit, Visitor, VisitorError};
-use rush_var::expand_env_vars;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -20,12 +18,11 @@ impl SourceScript {
 }
 
 impl Visit for SourceScript {
-    fn visit<'a>(&'a self, _context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        if !self.condition.check() {
+    fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        if !self.condition.check(&context.env)? {
             return Ok(());
         }
-        let expanded_file_path = expand_env_vars(&self.file);
-        let file = PathBuf::from(expanded_file_path);
+        let file = context.env.expand_path(Self::tag(), &self.file)?;
         if !file.is_file() {
             return Err(VisitorError::SourceFileNotExist(self.file.clone()));
         }
@@ -41,11 +41,12 @@ impl VarScript {
 }
 
 impl Visit for VarScript {
-    fn visit<'a>(&'a self, _context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        if !self.condition.check() {
+    fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
+        if !self.condition.check(&context.env)? {
             return Ok(());
         }
         writeln!(writer, r#"{}="{}""#, self.name, self.value)?;
+        context.env.set(&self.name, &self.value);
         Ok(())
     }
 }
@@ -34,13 +34,13 @@ impl Tool {
 
 impl Visit for Tool {
     fn visit<'a>(&'a self, context: &mut Visitor<'a>, writer: &mut impl std::io::Write) -> Result<(), VisitorError> {
-        if !self.condition.check() {
+        if !self.condition.check(&context.env)? {
             return Ok(());
         }
         if let Some(version) = &self.version {
             let name = format!("{}_VERSION", self.name.to_uppercase());
             let value = version.clone();
-            ExportScript::export(name, value, writer)?;
+            ExportScript::export(&mut context.env, name, value, writer)?;
         }
         self.paths.visit(context, writer)?;
         self.scripts.visit(context, writer)?;
@@ -1,3 +1,5 @@
+// Path 与 INITIALIZED_LOG 留给暂时停用的 init_log
+#[allow(unused_imports)]
 use std::path::{Path, PathBuf};
 use std::sync::Once;
 // use tracing_subscriber::EnvFilter;
@@ -6,9 +8,11 @@ use std::sync::Once;
 
 pub mod config;
 pub mod core;
+pub mod shell_env;
 pub mod visitor;
 
 static INITIALIZED_BACKTRACE: Once = Once::new();
+#[allow(dead_code)]
 static INITIALIZED_LOG: Once = Once::new();
 
 pub fn init_base_dir() -> PathBuf {
@@ -1,6 +1,6 @@
 mod cli;
 
-use crate::cli::{Cli, SubCmd};
+use crate::cli::Cli;
 use clap::Parser;
 use color_eyre::Result;
 use color_eyre::eyre::{OptionExt, WrapErr};
@@ -14,12 +14,11 @@ use std::path::{Path, PathBuf};
 const TEMPLATE: &str = include_str!("../assets/template/rush.xml");
 
 fn main() -> Result<()> {
-    let base_dir = init_base_dir();
+    let _base_dir = init_base_dir();
     init_backtrace();
-    // init_log(&base_dir);
+    // init_log(&_base_dir);
 
     let executable = Path::new(&std::env::args().next().ok_or_eyre("Executable name not found")?).canonicalize()?;
-    println!("# {}", executable.display());
 
     #[cfg(debug_assertions)]
     let rush_dir = unsafe {
@@ -28,14 +27,13 @@ fn main() -> Result<()> {
         PathBuf::from(rush_dir)
     };
     #[cfg(not(debug_assertions))]
-    let rush_dir = PathBuf::new(std::env::var("RUSH_DIR").wrap_err("RUSH_DIR environment variable must be set")?);
+    let rush_dir = PathBuf::from(std::env::var("RUSH_DIR").wrap_err("RUSH_DIR environment variable must be set")?);
 
     let cli = Cli::parse();
-    println!("{cli:#?}");
 
+    let rush: Rush = quick_xml::de::from_str(TEMPLATE)?;
     match cli.sub_cmd {
         None => {
-            let rush: Rush = quick_xml::de::from_str(TEMPLATE)?;
             let mut context = Visitor {
                 rush_dir,
                 section: Section::new(64, 2),
@@ -43,7 +41,7 @@ fn main() -> Result<()> {
             };
             rush.visit(&mut context, &mut stdout())?;
         }
-        Some(cmd) => cmd.execute(&executable)?,
+        Some(cmd) => cmd.execute(&rush, &rush_dir, &executable)?,
     }
 
     Ok(())
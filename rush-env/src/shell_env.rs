@@ -0,0 +1,216 @@
+use crate::visitor::VisitorError;
+use rush_var::ExpandOptions;
+use rush_var::ast::Dialect;
+use rush_var::command::{CommandRunner, ShellRunner};
+use rush_var::env_source::EnvSource;
+use rush_var::env_stack::EnvStack;
+use rush_var::error::ExpandError;
+use rush_var::home_source::SystemHome;
+use rush_var::os_env_source::OsEnvSource;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+
+/// 生成脚本期间模拟的 shell 环境（变量与 PATH）。
+///
+/// 进程环境只在创建时读取一次，作为只读的底层；`<export>`、`<path>` 等元素只修改这里，
+/// 之后的变量展开、`<has>` 等条件判断都读取这里，生成过程不会改动进程环境。
+pub struct ShellEnv {
+    stack: EnvStack<'static>,
+    /// 进程环境中值不是合法 UTF-8 的变量，只用于 [`ShellEnv::expand_path`]
+    non_utf8: HashMap<String, OsString>,
+}
+
+impl Default for ShellEnv {
+    fn default() -> Self {
+        Self::from_process()
+    }
+}
+
+impl Debug for ShellEnv {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.stack.vars()).finish()
+    }
+}
+
+impl ShellEnv {
+    /// 以当前进程环境为底层，值不是合法 UTF-8 的变量只在展开路径时可见，名字不是合法 UTF-8 的变量会被忽略
+    pub fn from_process() -> Self {
+        let mut vars = HashMap::new();
+        let mut non_utf8 = HashMap::new();
+        for (name, value) in std::env::vars_os() {
+            let Ok(name) = name.into_string() else {
+                continue;
+            };
+            match value.into_string() {
+                Ok(value) => {
+                    vars.insert(name, value);
+                }
+                Err(value) => {
+                    non_utf8.insert(name, value);
+                }
+            }
+        }
+        Self {
+            non_utf8,
+            ..Self::new(vars)
+        }
+    }
+
+    /// 以给定变量为底层，与进程环境无关，便于测试
+    pub fn new(vars: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>) -> Self {
+        let vars = vars
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect::<HashMap<String, String>>();
+        Self {
+            stack: EnvStack::new().with_source(vars),
+            non_utf8: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.stack.get(name).map(|value| value.into_owned())
+    }
+
+    /// 递归展开变量 `name` 的值，得到 shell 中该变量的实际值
+    pub fn resolve(&self, name: &str) -> Option<String> {
+        self.get(name).map(|value| rush_var::expand_env_recursive(&value, &self.stack))
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.stack.set(name, value);
+    }
+
+    /// 把 `paths` 追加到 PATH 末尾
+    pub fn append_paths(&mut self, paths: impl IntoIterator<Item = impl AsRef<OsStr>>) -> Result<(), VisitorError> {
+        let mut all = match self.get("PATH") {
+            Some(path) => std::env::split_paths(&path).collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+        all.extend(paths.into_iter().map(|path| PathBuf::from(path.as_ref())));
+        let path = std::env::join_paths(all)?;
+        self.set("PATH", path.to_string_lossy());
+        Ok(())
+    }
+
+    /// 在模拟的 PATH 中查找命令
+    pub fn which(&self, command: &str) -> Option<PathBuf> {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        which::which_in(command, self.get("PATH"), cwd).ok()
+    }
+
+    /// 按 zsh 语法展开 `value`（含 `~`、`~user` 与 `${VAR:h}` 等修饰符），失败时报告所在元素并标注出错位置。
+    /// `$(...)` 原样保留，由 shell 在启动时执行
+    pub fn expand(&self, tag: &'static str, value: &str) -> Result<String, VisitorError> {
+        self.expand_with(tag, value, None)
+    }
+
+    /// 同 [`ShellEnv::expand`]，但在生成时立即执行 `$(...)` 与反引号中的命令
+    pub fn eval(&self, tag: &'static str, value: &str) -> Result<String, VisitorError> {
+        self.expand_with(tag, value, Some(&ShellRunner::default()))
+    }
+
+    /// 同 [`ShellEnv::expand`]，但展开为文件路径，变量值中不是合法 UTF-8 的字节原样保留
+    pub fn expand_path(&self, tag: &'static str, value: &str) -> Result<PathBuf, VisitorError> {
+        rush_var::try_expand_os_recursive_with(OsStr::new(value), self, &Self::options(None))
+            .map(PathBuf::from)
+            .map_err(|source| Self::error(tag, value, source))
+    }
+
+    /// 按严格模式展开模板文本中的变量：未定义的变量报错，`$$` 为字面 `$`，变量值原样插入不再展开；
+    /// 不展开 `~`，`$(...)` 原样保留
+    pub fn render(&self, tag: &'static str, text: &str) -> Result<String, VisitorError> {
+        let options = ExpandOptions {
+            strict: true,
+            dialect: Dialect::Zsh,
+            ..Default::default()
+        };
+        rush_var::try_expand_with(text, self, &options).map_err(|source| Self::error(tag, text, source))
+    }
+
+    fn expand_with(&self, tag: &'static str, value: &str, runner: Option<&dyn CommandRunner>) -> Result<String, VisitorError> {
+        rush_var::try_expand_recursive_with(value, &self.stack, &Self::options(runner)).map_err(|source| Self::error(tag, value, source))
+    }
+
+    fn options(runner: Option<&dyn CommandRunner>) -> ExpandOptions<'_> {
+        ExpandOptions {
+            home: Some(&SystemHome),
+            runner,
+            dialect: Dialect::Zsh,
+            ..Default::default()
+        }
+    }
+
+    fn error(tag: &'static str, value: &str, source: ExpandError) -> VisitorError {
+        VisitorError::Expand {
+            tag,
+            value: value.to_string(),
+            source,
+        }
+    }
+}
+
+impl EnvSource for ShellEnv {
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.stack.get(key)
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        self.stack.vars().into_iter().collect()
+    }
+}
+
+impl OsEnvSource for ShellEnv {
+    fn get_os(&self, key: &str) -> Option<Cow<'_, OsStr>> {
+        match self.stack.get(key) {
+            Some(Cow::Borrowed(value)) => Some(Cow::Borrowed(OsStr::new(value))),
+            Some(Cow::Owned(value)) => Some(Cow::Owned(OsString::from(value))),
+            None => self.non_utf8.get(key).map(|value| Cow::Borrowed(value.as_os_str())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_append_paths() {
+        let mut env = ShellEnv::new([("HOME", "/home/me"), ("PATH", "/usr/bin")]);
+        env.set("GOPATH", "$HOME/go");
+        env.append_paths(["/opt/bin", "/home/me/go/bin"]).unwrap();
+        assert_eq!("/home/me/go/bin", env.expand("<test>", "$GOPATH/bin").unwrap());
+        assert_eq!(Some("/usr/bin:/opt/bin:/home/me/go/bin".to_string()), env.get("PATH"));
+        assert_eq!("/home/me", env.expand("<test>", "${GOPATH:h}").unwrap());
+        assert_eq!(None, env.which("rush-command-that-does-not-exist"));
+        let mut env = ShellEnv::new([("HOME", "/home/me")]);
+        env.append_paths(["/opt/bin"]).unwrap();
+        assert_eq!(Some("/opt/bin".to_string()), env.get("PATH"));
+    }
+
+    #[test]
+    fn test_which_uses_simulated_path() {
+        let env = ShellEnv::new([("PATH", "/rush/no/such/dir")]);
+        assert_eq!(None, env.which("sh"));
+        let env = ShellEnv::new([("PATH", "/bin:/usr/bin")]);
+        assert!(env.which("sh").is_some());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_expand_path_keeps_non_utf8_value() {
+        use std::os::unix::ffi::OsStrExt;
+        let mut env = ShellEnv::new([("HOME", "/home/me")]);
+        let dir = OsStr::from_bytes(b"/data/caf\xe9");
+        env.non_utf8.insert("DATA".to_string(), dir.to_os_string());
+        assert_eq!(
+            env.expand_path("<test>", "${DATA}/.env").unwrap().as_os_str().as_bytes(),
+            b"/data/caf\xe9/.env"
+        );
+        assert_eq!(PathBuf::from("/home/me/x"), env.expand_path("<test>", "~/x").unwrap());
+        assert!(env.expand_path("<test>", "${DATA").is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::core::path::Path;
+use crate::shell_env::ShellEnv;
 use rush_say::Section;
+use rush_var::error::{DotenvError, ExpandError};
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -9,6 +11,7 @@ pub struct Visitor<'a> {
     pub section: Section,
     pub paths: Vec<&'a Path>,
     pub plugin_work_dirs: Vec<&'a str>,
+    pub env: ShellEnv,
 }
 
 pub trait Visit {
@@ -26,6 +29,31 @@ pub enum VisitorError {
     #[error("Not found source file: {0}.")]
     SourceFileNotExist(String),
 
+    #[error("Not found env file: {0}.")]
+    EnvFileNotExist(String),
+
+    #[error("Cannot load env file '{path}': {source}")]
+    Dotenv { path: String, source: Box<DotenvError> },
+
+    #[error("Not found dotfile source: {0}.")]
+    DotfileSourceNotExist(String),
+
+    #[error("Cannot link built-in template '{0}', use mode=\"copy\" or mode=\"render\" instead.")]
+    LinkEmbeddedDotfile(String),
+
+    #[error("Cannot link dotfile '{0}': symbolic links are not supported on this platform, use mode=\"copy\" instead.")]
+    LinkUnsupported(String),
+
+    #[error("'<port_open>' is probed when the shell starts and is only supported in the <proxy> condition.")]
+    RuntimeCondition,
+
+    #[error("Cannot expand {tag} '{value}': {source}\n{annotated}", annotated = .source.annotate(.value))]
+    Expand {
+        tag: &'static str,
+        value: String,
+        source: ExpandError,
+    },
+
     #[error(transparent)]
     JoinPathsError(#[from] std::env::JoinPathsError),
 
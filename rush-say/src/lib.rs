@@ -8,9 +8,34 @@
 //! use rush_say::say_section;
 //! say_section(&mut stdout(), "你好，Rush!\n可自动居中、自动分行。", 48, 2).unwrap();
 //! ```
+//!
+//! ## 对齐与折行
+//! ```rust
+//! use rush_say::{Align, Section};
+//! let mut buf = Vec::new();
+//! Section::new(20, 1).with_align(Align::Left).say(&mut buf, "word aware wrapping").unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), "#------------------#\n# word aware       #\n# wrapping         #\n#------------------#\n");
+//! ```
+//!
+//! ## 边框样式与注释格式
+//! ```rust
+//! use rush_say::{BoxStyle, CommentStyle, Section};
+//! let section = Section::new(20, 1).with_style(BoxStyle::Rounded).with_comment(CommentStyle::Slash);
+//! let mut buf = Vec::new();
+//! section.say(&mut buf, "Rush").unwrap();
+//! assert_eq!(String::from_utf8(buf).unwrap(), "// ╭───────────────╮\n// │     Rush      │\n// ╰───────────────╯\n");
+//! ```
 
 mod section;
+mod style;
+mod table;
+mod wrap;
 pub use section::*;
+pub use style::{Align, BoxStyle, CommentStyle, ParseStyleError, Wrap};
+pub use table::Table;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// 输出漂亮的注释框气泡（支持自动分行、视觉居中、中文/emoji等宽）
 ///
@@ -18,68 +43,70 @@ pub use section::*;
 /// - `content`: 任意多行字符串
 /// - `width`/`padding`：可选参数（默认48/2）可自定义
 pub fn say_section(writer: &mut impl std::io::Write, content: &str, width: usize, padding: usize) -> std::io::Result<()> {
-    let max_line_width = width - 2 - padding * 2;
-    let border = format!("#{}#", "-".repeat(width - 2));
-    writeln!(writer, "{border}")?;
-
-    let mut chars = content.chars().peekable();
-    loop {
-        while chars.peek().is_some() && *chars.peek().unwrap() == '\n' {
-            write!(writer, "#")?;
-            for _ in 0..(width - 2) {
-                write!(writer, " ")?;
-            }
-            writeln!(writer, "#")?;
-            chars.next();
-        }
-        if chars.peek().is_none() {
-            break;
-        }
-        let mut current = String::new();
-        let mut visual = 0;
-        while let Some(&ch) = chars.peek() {
-            if ch == '\n' {
-                break;
-            }
-            let ch_width = visual_width_char(ch);
-            if visual + ch_width > max_line_width {
-                break;
-            }
-            visual += ch_width;
-            current.push(ch);
-            chars.next();
-        }
-        if chars.peek() == Some(&'\n') {
-            chars.next();
-        }
-        let spaces = max_line_width - visual;
-        let left = padding + spaces / 2;
-        let right = padding + (spaces - spaces / 2);
-        write!(writer, "#")?;
-        for _ in 0..left {
-            write!(writer, " ")?;
-        }
-        write!(writer, "{current}")?;
-        for _ in 0..right {
-            write!(writer, " ")?;
-        }
-        writeln!(writer, "#")?;
-    }
-    writeln!(writer, "{border}")?;
-    Ok(())
+    Section::new(width, padding).say(writer, content)
 }
 
-/// 视觉宽度换算（可随时自定义规则）
-fn visual_width_char(ch: char) -> usize {
-    match ch {
-        '\u{4e00}'..='\u{9fff}'   // CJK汉字
-        | '\u{3000}'..='\u{303f}' // CJK标点
-        | '\u{3040}'..='\u{30ff}' // 日文
-        | '\u{ff00}'..='\u{ffef}' // 全角
-        => 2,
-        '\u{1f300}'..='\u{1f6ff}' | '\u{1f900}'..='\u{1f9ff}' => 2, // emoji
-        _ => 1,
+/// 输出表格（`#` 注释、ASCII 边框），需要其它样式时使用 [`Table`]
+///
+/// - `headers`: 表头，为空时不输出表头行
+/// - `aligns`: 各列的对齐方式，未指定的列左对齐
+/// - `rows`: 各行的单元格
+/// - `max_width`：整行的最大视觉宽度，超出时从最宽的列开始收窄并以 `…` 截断
+///
+/// ```rust
+/// use rush_say::{Align, say_table};
+/// let mut buf = Vec::new();
+/// let rows = [["rust", "1.89.0"], ["node", "22.11.0-nightly"]];
+/// say_table(&mut buf, &["lang", "version"], &[Align::Left, Align::Right], rows, 26).unwrap();
+/// assert_eq!(
+///     String::from_utf8(buf).unwrap(),
+///     "\
+/// ##------+-----------------#
+/// ## lang |         version #
+/// ##------+-----------------#
+/// ## rust |          1.89.0 #
+/// ## node | 22.11.0-nightly #
+/// ##------+-----------------#
+/// "
+/// );
+/// ```
+pub fn say_table<R, S>(
+    writer: &mut impl std::io::Write,
+    headers: &[&str],
+    aligns: &[Align],
+    rows: impl IntoIterator<Item = R>,
+    max_width: usize,
+) -> std::io::Result<()>
+where
+    R: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut table = Table::new(headers.iter().copied())
+        .with_aligns(aligns.iter().copied())
+        .with_max_width(max_width);
+    for row in rows {
+        table.push_row(row);
     }
+    table.say(writer)
+}
+
+/// 文本在等宽终端中占用的列数。
+///
+/// 按扩展字素簇计算：ZWJ 连接的 emoji 序列、带肤色修饰或变体选择符 `U+FE0F` 的 emoji、国旗均算作一个整体，
+/// 单个字素簇的宽度遵循 Unicode East Asian Width 与 emoji 呈现规则（宽字符、全角与默认 emoji 呈现为 2，组合字符为 0）。
+///
+/// ```rust
+/// use rush_say::visual_width;
+/// assert_eq!(visual_width("🧑‍💻 Rush"), 7);
+/// assert_eq!(visual_width("🛠️한글"), 6);
+/// ```
+pub fn visual_width(text: &str) -> usize {
+    text.graphemes(true).map(grapheme_width).sum()
+}
+
+/// 单个字素簇的宽度
+pub(crate) fn grapheme_width(grapheme: &str) -> usize {
+    UnicodeWidthStr::width(grapheme)
 }
 
 #[cfg(test)]
@@ -184,6 +211,122 @@ r#"#----------------------------------------------#
         assert_eq!(expected, String::from_utf8(buf.into_inner()).unwrap());
     }
 
+    #[test]
+    fn test_section_styles_and_comments() {
+        let say = |section: Section| {
+            let mut buf = Vec::new();
+            section.say(&mut buf, "Rush\n配置").unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+        #[rustfmt::skip]
+        let cases = [
+            (BoxStyle::Single, CommentStyle::None, "┌────────────────┐\n│      Rush      │\n│      配置      │\n└────────────────┘\n"),
+            (BoxStyle::Double, CommentStyle::Hash, "# ╔══════════════╗\n# ║     Rush     ║\n# ║     配置     ║\n# ╚══════════════╝\n"),
+            (BoxStyle::Heavy, CommentStyle::Html, "<!-- ┏━━━━━━━┓ -->\n<!-- ┃ Rush  ┃ -->\n<!-- ┃ 配置  ┃ -->\n<!-- ┗━━━━━━━┛ -->\n"),
+            (BoxStyle::Ascii, CommentStyle::Dash, "------------------\n--     Rush     --\n--     配置     --\n------------------\n"),
+            (BoxStyle::Ascii, CommentStyle::Semicolon, ";----------------;\n;      Rush      ;\n;      配置      ;\n;----------------;\n"),
+            (BoxStyle::Ascii, CommentStyle::None, "+----------------+\n|      Rush      |\n|      配置      |\n+----------------+\n"),
+            (BoxStyle::Ascii, CommentStyle::Html, "<!-- +=======+ -->\n<!-- | Rush  | -->\n<!-- | 配置  | -->\n<!-- +=======+ -->\n"),
+        ];
+        for (style, comment, expected) in cases {
+            assert_eq!(
+                expected,
+                say(Section::new(18, 1).with_style(style).with_comment(comment)),
+                "{style:?} {comment:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_section_ascii_fallback() {
+        let mut buf = Vec::new();
+        let section = Section::new(30, 2).with_style(BoxStyle::Rounded).with_ascii(true);
+        section.say(&mut buf, "🛠️ Tools Section 🛠️\n🧑‍💻 中文 ✨").unwrap();
+        #[rustfmt::skip]
+        let expected =
+r#"#----------------------------#
+#        Tools Section       #
+#            中文            #
+#----------------------------#
+"#;
+        assert_eq!(expected, String::from_utf8(buf).unwrap());
+        // 默认以文本呈现的符号与空白原样保留
+        let output = Section::new(30, 0)
+            .with_align(Align::Left)
+            .with_ascii(true)
+            .render("✓ done ← 中文  ❤️x 1️⃣🇨🇳");
+        assert_eq!("#✓ done ← 中文  x            #", output.lines().nth(1).unwrap());
+        assert_eq!(Ok(CommentStyle::Slash), "//".parse());
+        assert_eq!(
+            "unknown style 'box', expected one of: ascii, single, double, rounded, heavy",
+            "box".parse::<BoxStyle>().unwrap_err().to_string()
+        );
+        assert_eq!(
+            "unknown wrap mode 'left', expected one of: word, char",
+            "left".parse::<Wrap>().unwrap_err().to_string()
+        );
+        assert_eq!(
+            "unknown alignment 'word', expected one of: left, center, right, justify",
+            "word".parse::<Align>().unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_visual_width_grapheme_clusters() {
+        #[rustfmt::skip]
+        let cases = [
+            ("🧑‍💻", 2),           // ZWJ 序列
+            ("👨‍👩‍👧‍👦", 2),
+            ("🛠️", 2),            // 默认文本呈现 + U+FE0F
+            ("🛠", 1),
+            ("☀️", 2),
+            ("⌚", 2),            // 默认 emoji 呈现
+            ("👍🏽", 2),           // 肤色修饰
+            ("🇨🇳", 2),           // 国旗
+            ("1️⃣", 2),           // 键帽序列
+            ("한국어", 6),        // 谚文
+            ("ｶﾀｶﾅ", 4),          // 半角片假名
+            ("e\u{301}", 1),      // 组合字符
+            ("中文，ＡＢ", 10),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(expected, visual_width(text), "{text:?}");
+        }
+    }
+
+    /// rush-env 生成脚本时输出的各段标题，每行都必须恰好 64 列
+    #[test]
+    fn test_section_rush_env_banners() {
+        let banners = [
+            "🌐 Proxy Section 🌐",
+            "🚀 Plugins Section 🚀",
+            "🔖 Functions Section  🔖",
+            "✨ Aliases Section ✨",
+            "🌱 Environment Variables Section 🌱",
+            "🧑‍💻 Languages Section 🧑‍💻",
+            "🛠️ Tools Section 🛠️",
+        ];
+        for banner in banners {
+            let mut buf = Vec::new();
+            Section::new(64, 2).say(&mut buf, banner).unwrap();
+            let output = String::from_utf8(buf).unwrap();
+            assert!(output.lines().all(|line| visual_width(line) == 64), "{output}");
+        }
+
+        let mut buf = Vec::new();
+        Section::new(64, 2)
+            .say(&mut buf, "🧑‍💻 Languages Section 🧑‍💻\n🛠️ Tools Section 🛠️")
+            .unwrap();
+        #[rustfmt::skip]
+        let expected =
+r#"#--------------------------------------------------------------#
+#                   🧑‍💻 Languages Section 🧑‍💻                    #
+#                     🛠️ Tools Section 🛠️                      #
+#--------------------------------------------------------------#
+"#;
+        assert_eq!(expected, String::from_utf8(buf).unwrap());
+    }
+
     #[test]
     fn test_section_preserve_empty_lines() {
         let mut buf = Cursor::new(Vec::new());
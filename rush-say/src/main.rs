@@ -1,4 +1,5 @@
 use clap::Parser;
+use rush_say::{Align, BoxStyle, CommentStyle, Section, Table, Wrap};
 use std::io::{self, Read, stdout};
 
 /// 终端气泡注释输出工具，支持自动换行、视觉居中、中文emoji宽度处理。
@@ -14,8 +15,44 @@ struct Cli {
     width: usize,
 
     /// 指定内容与边框的padding
-    #[arg(short, long, value_name = "PADDING", default_value_t = 2)]
+    #[arg(short, long, value_name = "PADDING", default_value_t = 2, conflicts_with = "table")]
     padding: usize,
+
+    /// 边框样式：ascii、single、double、rounded、heavy
+    #[arg(short, long, value_name = "STYLE", default_value = "ascii")]
+    style: BoxStyle,
+
+    /// 注释格式：hash(#)、slash(//)、dash(--)、semicolon(;)、html(<!-- -->)、none
+    #[arg(short, long, value_name = "COMMENT", default_value = "hash")]
+    comment: CommentStyle,
+
+    /// 对齐方式：left、center、right、justify；默认居中，表格模式下用于所有列，默认左对齐
+    #[arg(short, long, value_name = "ALIGN")]
+    align: Option<Align>,
+
+    /// 折行方式：word（按词断行，中日韩文字可逐字断开）、char（任意位置断行）
+    #[arg(long, value_name = "WRAP", default_value = "word", conflicts_with = "table")]
+    wrap: Wrap,
+
+    /// 嵌在上边框中的标题
+    #[arg(long, value_name = "TITLE", conflicts_with = "table")]
+    title: Option<String>,
+
+    /// 嵌在下边框中的页脚
+    #[arg(long, value_name = "FOOTER", conflicts_with = "table")]
+    footer: Option<String>,
+
+    /// 表格模式：每行一条记录，首行为表头，单元格以 --delimiter 分隔；--width 为最大宽度
+    #[arg(short, long)]
+    table: bool,
+
+    /// 表格模式下的单元格分隔符
+    #[arg(short, long, value_name = "DELIMITER", default_value = "\t", requires = "table")]
+    delimiter: String,
+
+    /// 不输出 emoji：使用 ascii 边框并去掉以 emoji 形式呈现的字符，适用于无法显示 emoji 的终端
+    #[arg(long)]
+    ascii: bool,
 }
 
 fn main() -> color_eyre::Result<()> {
@@ -31,6 +68,37 @@ fn main() -> color_eyre::Result<()> {
         content = content.trim_end().to_string(); // 去掉末尾多余空行
     }
 
-    rush_say::say_section(&mut stdout(), &content, cli.width, cli.padding)?;
+    if cli.table {
+        let mut lines = content.lines().map(|line| line.split(cli.delimiter.as_str()));
+        let headers: Vec<_> = lines.next().into_iter().flatten().collect();
+        let aligns = vec![cli.align.unwrap_or(Align::Left); headers.len()];
+        let mut table = Table::new(headers)
+            .with_aligns(aligns)
+            .with_max_width(cli.width)
+            .with_style(cli.style)
+            .with_comment(cli.comment)
+            .with_ascii(cli.ascii);
+        for row in lines {
+            table.push_row(row);
+        }
+        table.say(&mut stdout())?;
+        return Ok(());
+    }
+
+    let mut builder = Section::builder()
+        .width(cli.width)
+        .padding(cli.padding)
+        .style(cli.style)
+        .comment(cli.comment)
+        .ascii(cli.ascii)
+        .align(cli.align.unwrap_or_default())
+        .wrap(cli.wrap);
+    if let Some(title) = cli.title {
+        builder = builder.title(title);
+    }
+    if let Some(footer) = cli.footer {
+        builder = builder.footer(footer);
+    }
+    builder.build()?.say(&mut stdout(), &content)?;
     Ok(())
 }
@@ -1,23 +1,385 @@
-use crate::say_section;
+use crate::style::{Align, BoxStyle, CommentStyle, Frame, Wrap, strip_emoji};
+use crate::visual_width;
+use crate::wrap::wrap;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter, Write as _};
 
 #[derive(Debug, Clone)]
 pub struct Section {
     pub width: usize,
     pub padding: usize,
+    pub style: BoxStyle,
+    pub comment: CommentStyle,
+    /// 不输出 emoji：强制使用 [`BoxStyle::Ascii`]，并去掉内容中以 emoji 形式呈现的字符
+    pub ascii: bool,
+    pub align: Align,
+    pub wrap: Wrap,
+    /// 居中嵌入上边框的标题
+    pub title: Option<String>,
+    /// 居中嵌入下边框的页脚
+    pub footer: Option<String>,
 }
 
 impl Default for Section {
     fn default() -> Self {
-        Section { width: 48, padding: 2 }
+        Section::new(48, 2)
     }
 }
 
 impl Section {
     pub fn new(width: usize, padding: usize) -> Self {
-        Section { width, padding }
+        Section {
+            width,
+            padding,
+            style: BoxStyle::default(),
+            comment: CommentStyle::default(),
+            ascii: false,
+            align: Align::default(),
+            wrap: Wrap::default(),
+            title: None,
+            footer: None,
+        }
     }
 
+    /// 带参数校验的构建器
+    pub fn builder() -> SectionBuilder {
+        SectionBuilder::default()
+    }
+
+    pub fn with_style(mut self, style: BoxStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_comment(mut self, comment: CommentStyle) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    pub fn with_ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    /// 输出注释框，每行（含注释符与边框）的视觉宽度均为 `width`
     pub fn say(&self, writer: &mut impl std::io::Write, content: impl AsRef<str>) -> std::io::Result<()> {
-        say_section(writer, content.as_ref(), self.width, self.padding)
+        writer.write_all(self.render(content).as_bytes())
+    }
+
+    /// 渲染为字符串，以换行结尾。
+    ///
+    /// 对任意参数与内容都会结束且不会 panic：宽度不足时每行至少放入一个字素簇，此时该行会超出 `width`；
+    /// 放不下的标题与页脚会被省略。通过 [`SectionBuilder::build`] 校验过的参数不会出现这些情况。
+    pub fn render(&self, content: impl AsRef<str>) -> String {
+        let (style, content) = match self.ascii {
+            true => (BoxStyle::Ascii, Cow::Owned(strip_emoji(content.as_ref()))),
+            false => (self.style, Cow::Borrowed(content.as_ref())),
+        };
+        // 制表符的显示宽度取决于终端，与表格单元格一样替换为空格，保证边框对齐
+        let content = match content.contains('\t') {
+            true => Cow::Owned(content.replace('\t', " ")),
+            false => content,
+        };
+        let (title, footer) = (self.label(&self.title), self.label(&self.footer));
+        let frame = Frame::new(style, self.comment, self.width, title.as_deref(), footer.as_deref());
+        let inner_width = self.width.saturating_sub(frame.sides_width());
+        let max_line_width = inner_width.saturating_sub(self.padding * 2);
+        let mut output = String::new();
+        // 写入 String 不会失败
+        let _ = writeln!(output, "{}", frame.top);
+
+        let padding = " ".repeat(self.padding);
+        for paragraph in content.lines() {
+            let lines = wrap(paragraph, max_line_width, self.wrap);
+            let count = lines.len();
+            for (i, line) in lines.iter().enumerate() {
+                let text = line.render(self.align, max_line_width, i + 1 == count);
+                let _ = writeln!(output, "{}{padding}{text}{padding}{}", frame.left, frame.right);
+            }
+        }
+        let _ = writeln!(output, "{}", frame.bottom);
+        output
+    }
+
+    fn label<'a>(&self, label: &'a Option<String>) -> Option<Cow<'a, str>> {
+        let label = label.as_deref()?;
+        Some(match self.ascii {
+            true => Cow::Owned(strip_emoji(label)),
+            false => Cow::Borrowed(label),
+        })
+    }
+}
+
+/// [`Section`] 的构建器，[`build`](SectionBuilder::build) 时校验参数
+///
+/// ```rust
+/// use rush_say::{Align, BoxStyle, CommentStyle, Section, SectionError};
+/// let section = Section::builder()
+///     .width(24)
+///     .padding(1)
+///     .style(BoxStyle::Single)
+///     .comment(CommentStyle::None)
+///     .align(Align::Left)
+///     .title("Rush")
+///     .build()
+///     .unwrap();
+/// assert_eq!(section.render("ok"), "┌──────── Rush ────────┐\n│ ok                   │\n└──────────────────────┘\n");
+///
+/// let error = Section::builder().width(6).padding(2).build().unwrap_err();
+/// assert_eq!(error, SectionError::TooNarrow { width: 6, min_width: 8 });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SectionBuilder {
+    section: Section,
+}
+
+impl SectionBuilder {
+    /// 整行（含注释符与边框）的视觉宽度，默认 48
+    pub fn width(mut self, width: usize) -> Self {
+        self.section.width = width;
+        self
+    }
+
+    /// 内容与左右边框之间的空格数，默认 2
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.section.padding = padding;
+        self
+    }
+
+    pub fn style(mut self, style: BoxStyle) -> Self {
+        self.section.style = style;
+        self
+    }
+
+    pub fn comment(mut self, comment: CommentStyle) -> Self {
+        self.section.comment = comment;
+        self
+    }
+
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.section.ascii = ascii;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.section.align = align;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: Wrap) -> Self {
+        self.section.wrap = wrap;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.section.title = Some(title.into());
+        self
+    }
+
+    pub fn footer(mut self, footer: impl Into<String>) -> Self {
+        self.section.footer = Some(footer.into());
+        self
+    }
+
+    /// 校验内容区至少能放下一个宽字符（2 列），标题与页脚为单行且能放进上下边框
+    pub fn build(self) -> Result<Section, SectionError> {
+        let section = self.section;
+        let style = if section.ascii { BoxStyle::Ascii } else { section.style };
+        let frame = Frame::new(style, section.comment, section.width, None, None);
+        let min_width = frame.sides_width() + section.padding * 2 + 2;
+        if section.width < min_width {
+            return Err(SectionError::TooNarrow {
+                width: section.width,
+                min_width,
+            });
+        }
+        for label in [section.label(&section.title), section.label(&section.footer)]
+            .into_iter()
+            .flatten()
+        {
+            if label.contains('\n') || visual_width(&label) > frame.max_label_width() {
+                return Err(SectionError::LabelTooWide {
+                    label: label.into_owned(),
+                    max_width: frame.max_label_width(),
+                });
+            }
+        }
+        Ok(section)
+    }
+}
+
+/// [`SectionBuilder::build`] 的参数错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SectionError {
+    /// 去掉注释符、边框与 padding 后放不下一个宽字符
+    TooNarrow { width: usize, min_width: usize },
+    /// 标题或页脚含换行，或比上下边框的横线还宽
+    LabelTooWide { label: String, max_width: usize },
+}
+
+impl Display for SectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionError::TooNarrow { width, min_width } => {
+                write!(f, "section width {width} is too narrow, need at least {min_width}")
+            }
+            SectionError::LabelTooWide { label, max_width } => {
+                write!(f, "label {label:?} does not fit on a single line of {max_width} columns")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SectionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    /// 可显示的内容：单词、空白（含制表符）、中日韩文字、标点、emoji 序列与组合字符
+    fn printable() -> impl Strategy<Value = String> {
+        let piece = prop_oneof![
+            "[a-zA-Z0-9]{1,12}",
+            select(vec![" ", "  ", "\t", "\n", "\n\n"]).prop_map(str::to_string),
+            "[\u{4e00}-\u{9fff}]{1,8}",
+            select(vec!["，", "。", "、", "（", "）", ",", ".", "!", "?", "「", "」"]).prop_map(str::to_string),
+            select(vec!["🎉", "🧑‍💻", "🛠️", "🇨🇳", "1️⃣", "👍🏽", "e\u{301}", "한글", "ＡＢ", "ｶﾀｶﾅ"]).prop_map(str::to_string),
+        ];
+        proptest::collection::vec(piece, 0..40).prop_map(|pieces| pieces.concat())
+    }
+
+    fn section() -> impl Strategy<Value = Section> {
+        (
+            0..80usize,
+            0..8usize,
+            select(vec![
+                BoxStyle::Ascii,
+                BoxStyle::Single,
+                BoxStyle::Double,
+                BoxStyle::Rounded,
+                BoxStyle::Heavy,
+            ]),
+            select(vec![
+                CommentStyle::Hash,
+                CommentStyle::Slash,
+                CommentStyle::Dash,
+                CommentStyle::Semicolon,
+                CommentStyle::Html,
+                CommentStyle::None,
+            ]),
+            any::<bool>(),
+            select(vec![Align::Left, Align::Center, Align::Right, Align::Justify]),
+            select(vec![Wrap::Word, Wrap::Char]),
+            proptest::option::of("[a-zA-Z 中文🎉]{0,12}"),
+        )
+            .prop_map(|(width, padding, style, comment, ascii, align, wrap, title)| Section {
+                width,
+                padding,
+                style,
+                comment,
+                ascii,
+                align,
+                wrap,
+                footer: title.clone(),
+                title,
+            })
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        let error = Section::builder().width(7).padding(1).title("Rush").build().unwrap_err();
+        assert_eq!(
+            SectionError::LabelTooWide {
+                label: "Rush".into(),
+                max_width: 3
+            },
+            error
+        );
+        assert_eq!(r#"label "Rush" does not fit on a single line of 3 columns"#, error.to_string());
+        assert!(Section::builder().width(8).padding(1).title("Rush").build().is_ok());
+        assert!(Section::builder().width(30).footer("a\nb").build().is_err());
+        // ascii 模式下 emoji 会被去掉，不计入标题宽度
+        assert!(Section::builder().width(8).padding(1).ascii(true).title("Rush🎉").build().is_ok());
+        assert_eq!(
+            SectionError::TooNarrow { width: 12, min_width: 13 },
+            Section::builder()
+                .width(12)
+                .padding(0)
+                .comment(CommentStyle::Html)
+                .style(BoxStyle::Single)
+                .build()
+                .unwrap_err()
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_render_terminates(section in section(), content in any::<String>()) {
+            let output = section.render(&content);
+            prop_assert!(output.lines().count() >= 2);
+        }
+
+        #[test]
+        fn prop_built_sections_have_exact_width(section in section(), content in printable()) {
+            let Ok(section) = Section::builder()
+                .width(section.width)
+                .padding(section.padding)
+                .style(section.style)
+                .comment(section.comment)
+                .ascii(section.ascii)
+                .align(section.align)
+                .wrap(section.wrap)
+                .title(section.title.unwrap_or_default())
+                .build()
+            else {
+                return Ok(());
+            };
+            for line in section.render(&content).lines() {
+                // 制表符在终端中的宽度不定，不能出现在输出中
+                prop_assert!(!line.contains('\t'), "{:?}", line);
+                prop_assert_eq!(section.width, visual_width(line), "{:?}", line);
+            }
+        }
+
+        #[test]
+        fn prop_wrapping_keeps_content(
+            width in 4..60usize,
+            align in select(vec![Align::Left, Align::Center, Align::Right, Align::Justify]),
+            wrap in select(vec![Wrap::Word, Wrap::Char]),
+            content in printable(),
+        ) {
+            let section = Section::new(width, 0).with_comment(CommentStyle::None).with_align(align).with_wrap(wrap);
+            let output = section.render(&content);
+            let lines: Vec<&str> = output.lines().collect();
+            let rendered: String = lines[1..lines.len() - 1]
+                .iter()
+                .flat_map(|line| line.trim_start_matches('|').trim_end_matches('|').chars())
+                .filter(|ch| !ch.is_whitespace())
+                .collect();
+            let expected: String = content.chars().filter(|ch| !ch.is_whitespace()).collect();
+            prop_assert_eq!(expected, rendered);
+        }
     }
 }
@@ -0,0 +1,400 @@
+use crate::visual_width;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 边框样式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BoxStyle {
+    /// `+-|`；带行注释时直接以注释符作为左右边框，例如 `#----#`；在 `<!-- -->` 中横线为 `=`，例如 `<!-- +====+ -->`
+    #[default]
+    Ascii,
+    /// `┌─┐│└┘`
+    Single,
+    /// `╔═╗║╚╝`
+    Double,
+    /// `╭─╮│╰╯`
+    Rounded,
+    /// `┏━┓┃┗┛`
+    Heavy,
+}
+
+/// 每行的注释格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommentStyle {
+    /// `#`，shell、Python、TOML
+    #[default]
+    Hash,
+    /// `//`，C、Rust、JavaScript
+    Slash,
+    /// `--`，SQL、Lua、Haskell
+    Dash,
+    /// `;`，INI、Lisp、汇编
+    Semicolon,
+    /// `<!-- -->`，HTML、XML、Markdown
+    Html,
+    /// 不加注释
+    None,
+}
+
+/// 内容行的对齐方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    #[default]
+    Center,
+    Right,
+    /// 两端对齐：把多出的空格分配到词间空白，段落最后一行与没有词间空白的行按左对齐处理
+    Justify,
+}
+
+/// 超宽时的折行方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Wrap {
+    /// 在空白处断行，中日韩文字与 emoji 可逐字断开，标点不出现在行首；单词本身超宽时再硬折行
+    #[default]
+    Word,
+    /// 在任意字素簇之间断行
+    Char,
+}
+
+impl Align {
+    pub const ALL: [&'static str; 4] = ["left", "center", "right", "justify"];
+}
+
+impl Wrap {
+    pub const ALL: [&'static str; 2] = ["word", "char"];
+}
+
+/// 边框字符：左上、右上、左下、右下、横线、竖线
+struct BoxChars([char; 6]);
+
+impl BoxStyle {
+    pub const ALL: [&'static str; 5] = ["ascii", "single", "double", "rounded", "heavy"];
+
+    fn chars(self) -> BoxChars {
+        BoxChars(match self {
+            BoxStyle::Ascii => ['+', '+', '+', '+', '-', '|'],
+            BoxStyle::Single => ['┌', '┐', '└', '┘', '─', '│'],
+            BoxStyle::Double => ['╔', '╗', '╚', '╝', '═', '║'],
+            BoxStyle::Rounded => ['╭', '╮', '╰', '╯', '─', '│'],
+            BoxStyle::Heavy => ['┏', '┓', '┗', '┛', '━', '┃'],
+        })
+    }
+
+    /// 在 `comment` 中使用的边框字符：块注释 `<!-- -->` 中不能出现 `--`，ASCII 横线改用 `=`
+    fn chars_in(self, comment: CommentStyle) -> BoxChars {
+        let BoxChars(mut chars) = self.chars();
+        if self == BoxStyle::Ascii && !comment.close().is_empty() {
+            chars[4] = '=';
+        }
+        BoxChars(chars)
+    }
+
+    /// 表格交叉处的字符：上、下、左、右、中
+    fn joints(self) -> [char; 5] {
+        match self {
+            BoxStyle::Ascii => ['+'; 5],
+            BoxStyle::Single | BoxStyle::Rounded => ['┬', '┴', '├', '┤', '┼'],
+            BoxStyle::Double => ['╦', '╩', '╠', '╣', '╬'],
+            BoxStyle::Heavy => ['┳', '┻', '┣', '┫', '╋'],
+        }
+    }
+}
+
+impl CommentStyle {
+    pub const ALL: [&'static str; 6] = ["hash", "slash", "dash", "semicolon", "html", "none"];
+
+    /// 行首的注释符
+    pub fn open(self) -> &'static str {
+        match self {
+            CommentStyle::Hash => "#",
+            CommentStyle::Slash => "//",
+            CommentStyle::Dash => "--",
+            CommentStyle::Semicolon => ";",
+            CommentStyle::Html => "<!--",
+            CommentStyle::None => "",
+        }
+    }
+
+    /// 行尾的注释符，只有块注释才有
+    pub fn close(self) -> &'static str {
+        match self {
+            CommentStyle::Html => "-->",
+            _ => "",
+        }
+    }
+}
+
+/// 由样式与注释格式组合出的边框，`left`/`right` 为内容行两侧的文本（均为 ASCII 或单宽字符）
+pub(crate) struct Frame {
+    pub top: String,
+    pub bottom: String,
+    pub left: String,
+    pub right: String,
+    /// 上下边框中横线部分的宽度，标题与页脚嵌在其中
+    pub fill_width: usize,
+}
+
+impl Frame {
+    /// `width` 为整行（含注释符）的视觉宽度，`title`/`footer` 居中嵌入上下边框，放不下时省略
+    pub fn new(style: BoxStyle, comment: CommentStyle, width: usize, title: Option<&str>, footer: Option<&str>) -> Self {
+        let open = comment.open();
+        if style == BoxStyle::Ascii && !open.is_empty() && comment.close().is_empty() {
+            // 行注释左右对称，例如 `#  标题  #`、`--  标题  --`
+            return Frame {
+                top: border(open, '-', open, width, title),
+                bottom: border(open, '-', open, width, footer),
+                left: open.to_string(),
+                right: open.to_string(),
+                fill_width: width.saturating_sub(open.len() * 2),
+            };
+        }
+        let prefix = if open.is_empty() { String::new() } else { format!("{open} ") };
+        let suffix = match comment.close() {
+            "" => String::new(),
+            close => format!(" {close}"),
+        };
+        let BoxChars([top_left, top_right, bottom_left, bottom_right, horizontal, vertical]) = style.chars_in(comment);
+        Frame {
+            top: border(
+                &format!("{prefix}{top_left}"),
+                horizontal,
+                &format!("{top_right}{suffix}"),
+                width,
+                title,
+            ),
+            bottom: border(
+                &format!("{prefix}{bottom_left}"),
+                horizontal,
+                &format!("{bottom_right}{suffix}"),
+                width,
+                footer,
+            ),
+            left: format!("{prefix}{vertical}"),
+            right: format!("{vertical}{suffix}"),
+            fill_width: width.saturating_sub(prefix.len() + suffix.len() + 2),
+        }
+    }
+
+    /// 左右两侧占用的视觉宽度
+    pub fn sides_width(&self) -> usize {
+        self.left.chars().count() + self.right.chars().count()
+    }
+
+    /// 上下边框能容纳的标题宽度（两侧各留一个空格）
+    pub fn max_label_width(&self) -> usize {
+        self.fill_width.saturating_sub(2)
+    }
+}
+
+/// 横向边框，`label` 两侧加空格后居中嵌入横线
+fn border(left: &str, fill: char, right: &str, width: usize, label: Option<&str>) -> String {
+    let fill_width = width.saturating_sub(left.chars().count() + right.chars().count());
+    let label = label
+        .map(|label| format!(" {label} "))
+        .filter(|label| !label.contains('\n') && visual_width(label) <= fill_width)
+        .unwrap_or_default();
+    let spaces = fill_width - visual_width(&label);
+    let fill = fill.to_string();
+    format!(
+        "{left}{}{label}{}{right}",
+        fill.repeat(spaces / 2),
+        fill.repeat(spaces - spaces / 2)
+    )
+}
+
+/// 表格的横线，`joint` 为列分隔处的字符
+pub(crate) struct Rule {
+    left: String,
+    fill: char,
+    joint: char,
+    right: String,
+}
+
+impl Rule {
+    /// `widths` 为各列（含左右各一个空格）的宽度
+    pub fn render(&self, widths: &[usize]) -> String {
+        let fill = self.fill.to_string();
+        let segments: Vec<String> = widths.iter().map(|&width| fill.repeat(width)).collect();
+        format!("{}{}{}", self.left, segments.join(&self.joint.to_string()), self.right)
+    }
+}
+
+/// 表格边框：上、表头下、底三条横线，以及内容行两侧与列之间的分隔符
+pub(crate) struct TableFrame {
+    pub top: Rule,
+    pub middle: Rule,
+    pub bottom: Rule,
+    pub left: String,
+    pub separator: char,
+    pub right: String,
+}
+
+impl TableFrame {
+    pub fn new(style: BoxStyle, comment: CommentStyle) -> Self {
+        let open = comment.open();
+        let BoxChars([top_left, top_right, bottom_left, bottom_right, horizontal, vertical]) = style.chars_in(comment);
+        if style == BoxStyle::Ascii && !open.is_empty() && comment.close().is_empty() {
+            // 同 Frame：以行注释符作为左右边框，例如 `#----+----#`、`# a  | b  #`
+            let rule = || Rule {
+                left: open.to_string(),
+                fill: '-',
+                joint: '+',
+                right: open.to_string(),
+            };
+            return TableFrame {
+                top: rule(),
+                middle: rule(),
+                bottom: rule(),
+                left: open.to_string(),
+                separator: vertical,
+                right: open.to_string(),
+            };
+        }
+        let prefix = if open.is_empty() { String::new() } else { format!("{open} ") };
+        let suffix = match comment.close() {
+            "" => String::new(),
+            close => format!(" {close}"),
+        };
+        let [top_joint, bottom_joint, left_joint, right_joint, cross] = style.joints();
+        let rule = |left: char, joint: char, right: char| Rule {
+            left: format!("{prefix}{left}"),
+            fill: horizontal,
+            joint,
+            right: format!("{right}{suffix}"),
+        };
+        TableFrame {
+            top: rule(top_left, top_joint, top_right),
+            middle: rule(left_joint, cross, right_joint),
+            bottom: rule(bottom_left, bottom_joint, bottom_right),
+            left: format!("{prefix}{vertical}"),
+            separator: vertical,
+            right: format!("{vertical}{suffix}"),
+        }
+    }
+
+    /// 两侧与列分隔符占用的视觉宽度
+    pub fn chrome_width(&self, columns: usize) -> usize {
+        self.left.chars().count() + self.right.chars().count() + columns.saturating_sub(1)
+    }
+}
+
+/// 无法识别的样式名，`kind` 为选项的名称，例如 `style`、`alignment`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStyleError {
+    kind: &'static str,
+    value: String,
+    expected: &'static [&'static str],
+}
+
+impl Display for ParseStyleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown {} '{}', expected one of: {}",
+            self.kind,
+            self.value,
+            self.expected.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ParseStyleError {}
+
+impl FromStr for BoxStyle {
+    type Err = ParseStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(BoxStyle::Ascii),
+            "single" => Ok(BoxStyle::Single),
+            "double" => Ok(BoxStyle::Double),
+            "rounded" => Ok(BoxStyle::Rounded),
+            "heavy" => Ok(BoxStyle::Heavy),
+            _ => Err(ParseStyleError {
+                kind: "style",
+                value: s.to_string(),
+                expected: &Self::ALL,
+            }),
+        }
+    }
+}
+
+impl FromStr for CommentStyle {
+    type Err = ParseStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hash" | "#" => Ok(CommentStyle::Hash),
+            "slash" | "//" => Ok(CommentStyle::Slash),
+            "dash" | "--" => Ok(CommentStyle::Dash),
+            "semicolon" | ";" => Ok(CommentStyle::Semicolon),
+            "html" | "<!--" => Ok(CommentStyle::Html),
+            "none" => Ok(CommentStyle::None),
+            _ => Err(ParseStyleError {
+                kind: "comment style",
+                value: s.to_string(),
+                expected: &Self::ALL,
+            }),
+        }
+    }
+}
+
+impl FromStr for Align {
+    type Err = ParseStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Align::Left),
+            "center" => Ok(Align::Center),
+            "right" => Ok(Align::Right),
+            "justify" => Ok(Align::Justify),
+            _ => Err(ParseStyleError {
+                kind: "alignment",
+                value: s.to_string(),
+                expected: &Self::ALL,
+            }),
+        }
+    }
+}
+
+impl FromStr for Wrap {
+    type Err = ParseStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "word" => Ok(Wrap::Word),
+            "char" => Ok(Wrap::Char),
+            _ => Err(ParseStyleError {
+                kind: "wrap mode",
+                value: s.to_string(),
+                expected: &Self::ALL,
+            }),
+        }
+    }
+}
+
+/// 去掉以 emoji 形式呈现的字素簇（含 ZWJ 序列、肤色、国旗与键帽），用于无法显示 emoji 的终端；
+/// `✓`、`←` 等默认以文本呈现的符号、中日韩文字与空白都原样保留
+pub(crate) fn strip_emoji(content: &str) -> String {
+    content.graphemes(true).filter(|grapheme| !is_emoji(grapheme)).collect()
+}
+
+/// 带 emoji 变体选择符或键帽的序列、国旗，以及宽度为 2 的图形符号（即默认以 emoji 呈现的 Extended_Pictographic）
+fn is_emoji(grapheme: &str) -> bool {
+    if grapheme.contains(['\u{fe0f}', '\u{20e3}']) {
+        return true;
+    }
+    let Some(first) = grapheme.chars().next() else {
+        return false;
+    };
+    let pictographic = matches!(first,
+        '\u{2190}'..='\u{21ff}'     // 箭头
+        | '\u{2300}'..='\u{23ff}'   // 技术符号（⌚ ⏰ 等）
+        | '\u{2600}'..='\u{27bf}'   // 杂项符号与装饰符号
+        | '\u{2b00}'..='\u{2bff}'   // ⭐ ⬆ 等
+        | '\u{1f000}'..='\u{1faff}' // emoji 主体（含国旗的区域指示符）
+    );
+    pictographic && (visual_width(grapheme) >= 2 || ('\u{1f1e6}'..='\u{1f1ff}').contains(&first))
+}
@@ -0,0 +1,330 @@
+use crate::style::{Align, BoxStyle, CommentStyle, TableFrame, strip_emoji};
+use crate::{grapheme_width, visual_width};
+use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 表格：列宽按视觉宽度自动计算，边框样式与注释格式同 [`Section`](crate::Section)
+///
+/// ```rust
+/// use rush_say::{Align, BoxStyle, Table};
+/// let table = Table::new(["工具", "版本"])
+///     .with_row(["rust", "1.89.0"])
+///     .with_row(["🐍 python", "3.13"])
+///     .with_aligns([Align::Left, Align::Right])
+///     .with_style(BoxStyle::Rounded);
+/// assert_eq!(
+///     table.render(),
+///     "\
+/// ## ╭───────────┬────────╮
+/// ## │ 工具      │   版本 │
+/// ## ├───────────┼────────┤
+/// ## │ rust      │ 1.89.0 │
+/// ## │ 🐍 python │   3.13 │
+/// ## ╰───────────┴────────╯
+/// "
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    /// 表头，为空时不输出表头行
+    pub headers: Vec<String>,
+    /// 各行的单元格，缺少的单元格留空
+    pub rows: Vec<Vec<String>>,
+    /// 各列的对齐方式，未指定的列左对齐，[`Align::Justify`] 按左对齐处理
+    pub aligns: Vec<Align>,
+    /// 整行（含注释符与边框）的最大视觉宽度：超出时从最宽的列开始收窄，放不下的单元格以 `…` 截断；
+    /// 每列至少保留 1 列宽度，因此列数过多时仍可能超出
+    pub max_width: Option<usize>,
+    pub style: BoxStyle,
+    pub comment: CommentStyle,
+    /// 不输出 emoji：强制使用 [`BoxStyle::Ascii`]，去掉单元格中以 emoji 形式呈现的字符，并以 `...` 作为省略号
+    pub ascii: bool,
+}
+
+impl Table {
+    pub fn new<S: Into<String>>(headers: impl IntoIterator<Item = S>) -> Self {
+        Table {
+            headers: headers.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// 无表头的两列键值表
+    ///
+    /// ```rust
+    /// use rush_say::{CommentStyle, Table};
+    /// let table = Table::key_value([("RUSH_DIR", "~/.rush"), ("shell", "zsh")]).with_comment(CommentStyle::Slash);
+    /// assert_eq!(table.render(), "//----------+---------//\n// RUSH_DIR | ~/.rush //\n// shell    | zsh     //\n//----------+---------//\n");
+    /// ```
+    pub fn key_value<K: Into<String>, V: Into<String>>(pairs: impl IntoIterator<Item = (K, V)>) -> Self {
+        Table {
+            rows: pairs.into_iter().map(|(key, value)| vec![key.into(), value.into()]).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn push_row<S: Into<String>>(&mut self, row: impl IntoIterator<Item = S>) {
+        self.rows.push(row.into_iter().map(Into::into).collect());
+    }
+
+    pub fn with_row<S: Into<String>>(mut self, row: impl IntoIterator<Item = S>) -> Self {
+        self.push_row(row);
+        self
+    }
+
+    pub fn with_aligns(mut self, aligns: impl IntoIterator<Item = Align>) -> Self {
+        self.aligns = aligns.into_iter().collect();
+        self
+    }
+
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn with_style(mut self, style: BoxStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_comment(mut self, comment: CommentStyle) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    pub fn with_ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    pub fn say(&self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        writer.write_all(self.render().as_bytes())
+    }
+
+    /// 渲染为字符串，以换行结尾；没有任何列时返回空字符串
+    pub fn render(&self) -> String {
+        let style = if self.ascii { BoxStyle::Ascii } else { self.style };
+        let frame = TableFrame::new(style, self.comment);
+        let headers: Vec<Cow<str>> = self.headers.iter().map(|cell| self.clean(cell)).collect();
+        let rows: Vec<Vec<Cow<str>>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| self.clean(cell)).collect())
+            .collect();
+        let columns = rows.iter().map(Vec::len).chain([headers.len()]).max().unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+
+        let mut widths = vec![1; columns];
+        for row in rows.iter().chain([&headers]) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(visual_width(cell));
+            }
+        }
+        if let Some(max_width) = self.max_width {
+            shrink(&mut widths, max_width.saturating_sub(frame.chrome_width(columns) + columns * 2));
+        }
+
+        let ellipsis = if self.ascii { "..." } else { "…" };
+        let line = |row: &[Cow<str>]| {
+            let cells: Vec<String> = widths
+                .iter()
+                .enumerate()
+                .map(|(i, &width)| {
+                    let cell = row.get(i).map_or("", |cell| cell);
+                    let align = self.aligns.get(i).copied().unwrap_or(Align::Left);
+                    format!(" {} ", fit(cell, width, align, ellipsis))
+                })
+                .collect();
+            format!("{}{}{}\n", frame.left, cells.join(&frame.separator.to_string()), frame.right)
+        };
+        let rule_widths: Vec<usize> = widths.iter().map(|width| width + 2).collect();
+        let mut output = frame.top.render(&rule_widths) + "\n";
+        if !headers.is_empty() {
+            output += &line(&headers);
+            output += &(frame.middle.render(&rule_widths) + "\n");
+        }
+        for row in &rows {
+            output += &line(row);
+        }
+        output + &frame.bottom.render(&rule_widths) + "\n"
+    }
+
+    /// 单元格只占一行：换行与制表符替换为空格
+    fn clean<'a>(&self, cell: &'a str) -> Cow<'a, str> {
+        let cell = match cell.contains(['\n', '\r', '\t']) {
+            true => Cow::Owned(cell.replace("\r\n", " ").replace(['\n', '\r', '\t'], " ")),
+            false => Cow::Borrowed(cell),
+        };
+        match self.ascii {
+            true => Cow::Owned(strip_emoji(&cell)),
+            false => cell,
+        }
+    }
+}
+
+/// 每次把最宽的一列收窄 1，直到总宽度不超过 `budget` 或所有列都只剩 1
+fn shrink(widths: &mut [usize], budget: usize) {
+    while widths.iter().sum::<usize>() > budget {
+        let Some(widest) = widths.iter_mut().rev().max_by_key(|width| **width) else {
+            return;
+        };
+        if *widest <= 1 {
+            return;
+        }
+        *widest -= 1;
+    }
+}
+
+/// 截断到 `width` 列并按 `align` 补足空格
+fn fit(cell: &str, width: usize, align: Align, ellipsis: &str) -> String {
+    let cell = truncate(cell, width, ellipsis);
+    let spaces = width.saturating_sub(visual_width(&cell));
+    let (left, right) = match align {
+        Align::Left | Align::Justify => (0, spaces),
+        Align::Right => (spaces, 0),
+        Align::Center => (spaces / 2, spaces - spaces / 2),
+    };
+    format!("{}{cell}{}", " ".repeat(left), " ".repeat(right))
+}
+
+/// 超过 `width` 列时按字素簇截断并加上省略号，省略号也放不下时直接截断
+fn truncate<'a>(text: &'a str, width: usize, ellipsis: &str) -> Cow<'a, str> {
+    if visual_width(text) <= width {
+        return Cow::Borrowed(text);
+    }
+    let ellipsis = if visual_width(ellipsis) <= width { ellipsis } else { "" };
+    let budget = width - visual_width(ellipsis);
+    let mut used = 0;
+    let mut end = 0;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        used += grapheme_width(grapheme);
+        if used > budget {
+            break;
+        }
+        end = i + grapheme.len();
+    }
+    Cow::Owned(format!("{}{ellipsis}", text[..end].trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn plugins() -> Table {
+        Table::new(["Plugin", "Language", "Version"])
+            .with_row(["zsh-autosuggestions", "Shell", "0.7.1"])
+            .with_row(["🧑‍💻 rush-env", "Rust 🦀", "0.3.0-beta.1"])
+            .with_row(["中文插件", "", "1.0"])
+    }
+
+    #[test]
+    fn test_table_styles() {
+        #[rustfmt::skip]
+        let expected =
+r#"#---------------------+----------+--------------#
+# Plugin              | Language | Version      #
+#---------------------+----------+--------------#
+# zsh-autosuggestions | Shell    | 0.7.1        #
+# 🧑‍💻 rush-env         | Rust 🦀  | 0.3.0-beta.1 #
+# 中文插件            |          | 1.0          #
+#---------------------+----------+--------------#
+"#;
+        assert_eq!(expected, plugins().render());
+
+        #[rustfmt::skip]
+        let expected =
+r#"<!-- ┏━━━━━━━━━━━━━━━━━━━━━┳━━━━━━━━━━┳━━━━━━━━━━━━━━┓ -->
+<!-- ┃       Plugin        ┃ Language ┃      Version ┃ -->
+<!-- ┣━━━━━━━━━━━━━━━━━━━━━╋━━━━━━━━━━╋━━━━━━━━━━━━━━┫ -->
+<!-- ┃ zsh-autosuggestions ┃ Shell    ┃        0.7.1 ┃ -->
+<!-- ┃     🧑‍💻 rush-env     ┃ Rust 🦀  ┃ 0.3.0-beta.1 ┃ -->
+<!-- ┃      中文插件       ┃          ┃          1.0 ┃ -->
+<!-- ┗━━━━━━━━━━━━━━━━━━━━━┻━━━━━━━━━━┻━━━━━━━━━━━━━━┛ -->
+"#;
+        let table = plugins().with_style(BoxStyle::Heavy).with_comment(CommentStyle::Html).with_aligns([
+            Align::Center,
+            Align::Justify,
+            Align::Right,
+        ]);
+        assert_eq!(expected, table.render());
+
+        // 块注释中不能出现 `--`
+        let table = Table::new(["a", "b"]).with_row(["1", "2"]).with_comment(CommentStyle::Html);
+        #[rustfmt::skip]
+        let expected =
+r#"<!-- +===+===+ -->
+<!-- | a | b | -->
+<!-- +===+===+ -->
+<!-- | 1 | 2 | -->
+<!-- +===+===+ -->
+"#;
+        assert_eq!(expected, table.render());
+    }
+
+    #[test]
+    fn test_table_truncation() {
+        #[rustfmt::skip]
+        let expected =
+r#"┌───────────┬──────────┬───────────┐
+│ Plugin    │ Language │ Version   │
+├───────────┼──────────┼───────────┤
+│ zsh-auto… │ Shell    │ 0.7.1     │
+│ 🧑‍💻 rush-… │ Rust 🦀  │ 0.3.0-be… │
+│ 中文插件  │          │ 1.0       │
+└───────────┴──────────┴───────────┘
+"#;
+        let table = plugins()
+            .with_style(BoxStyle::Single)
+            .with_comment(CommentStyle::None)
+            .with_max_width(36);
+        assert_eq!(expected, table.render());
+
+        #[rustfmt::skip]
+        let expected =
+r#"#-------+-------+--------#
+# Pl... | La... | Ver... #
+#-------+-------+--------#
+# zs... | Shell | 0.7.1  #
+#  r... | Rust  | 0.3... #
+# 中... |       | 1.0    #
+#-------+-------+--------#
+"#;
+        assert_eq!(expected, plugins().with_ascii(true).with_max_width(26).render());
+
+        assert_eq!("中…", truncate("中文插件", 3, "…"));
+        assert_eq!("ab", truncate("abc", 2, "..."));
+        assert_eq!("a…", truncate("a bc", 3, "…"));
+    }
+
+    #[test]
+    fn test_table_ragged_rows_and_empty() {
+        let table = Table::new(["a"]).with_row(["1", "line\nbreak"]).with_row(Vec::<String>::new());
+        assert_eq!(
+            "#---+------------#\n# a |            #\n#---+------------#\n# 1 | line break #\n#   |            #\n#---+------------#\n",
+            table.render()
+        );
+        assert_eq!("", Table::default().render());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_rows_share_width(
+            cells in proptest::collection::vec("[a-z 中文🎉\t\n]{0,12}", 1..12),
+            columns in 1..4usize,
+            max_width in 0..60usize,
+        ) {
+            let mut table = Table::new(cells.iter().take(columns)).with_max_width(max_width);
+            for row in cells.chunks(columns).skip(1) {
+                table.push_row(row);
+            }
+            let output = table.render();
+            let widths: Vec<usize> = output.lines().map(visual_width).collect();
+            prop_assert!(widths.iter().all(|&width| width == widths[0]), "{}", output);
+            let min_width = TableFrame::new(BoxStyle::Ascii, CommentStyle::Hash).chrome_width(columns) + columns * 3;
+            prop_assert!(widths[0] <= max_width.max(min_width), "{}", output);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::style::{Align, Wrap};
+use crate::{grapheme_width, visual_width};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 不能出现在行首的标点
+const NO_START: &str = "，。、！？：；）」』》〉】〕…‥·,.!?:;)]}%’”";
+/// 不能出现在行尾的标点
+const NO_END: &str = "（「『《〈【〔([{‘“";
+
+/// 不可再拆分的片段，`gap` 为它前面的空白（在行首时丢弃）
+#[derive(Debug)]
+struct Piece<'a> {
+    gap: &'a str,
+    text: &'a str,
+}
+
+/// 折行后的一行
+#[derive(Debug, Default)]
+pub(crate) struct Line<'a> {
+    pieces: Vec<Piece<'a>>,
+}
+
+impl<'a> Line<'a> {
+    pub fn width(&self) -> usize {
+        self.pieces.iter().map(|piece| piece.gap.len() + visual_width(piece.text)).sum()
+    }
+
+    /// 按 `align` 补足到 `width` 列；`last` 为段落的最后一行，两端对齐时按左对齐处理
+    pub fn render(&self, align: Align, width: usize, last: bool) -> String {
+        let spaces = width.saturating_sub(self.width());
+        let (left, right) = match align {
+            Align::Left => (0, spaces),
+            Align::Right => (spaces, 0),
+            Align::Center => (spaces / 2, spaces - spaces / 2),
+            Align::Justify => return self.justify(width, last),
+        };
+        format!("{}{}{}", " ".repeat(left), self.text(), " ".repeat(right))
+    }
+
+    fn text(&self) -> String {
+        self.pieces.iter().flat_map(|piece| [piece.gap, piece.text]).collect()
+    }
+
+    /// 把多出的空格均匀分配到词间空白，靠前的空白多分一个
+    fn justify(&self, width: usize, last: bool) -> String {
+        let gaps = self.pieces.iter().skip(1).filter(|piece| !piece.gap.is_empty()).count();
+        let spaces = width.saturating_sub(self.width());
+        if last || gaps == 0 {
+            return format!("{}{}", self.text(), " ".repeat(spaces));
+        }
+        let mut line = String::new();
+        let mut gap_index = 0;
+        for (i, piece) in self.pieces.iter().enumerate() {
+            line.push_str(piece.gap);
+            if i > 0 && !piece.gap.is_empty() {
+                let extra = spaces / gaps + usize::from(gap_index < spaces % gaps);
+                line.push_str(&" ".repeat(extra));
+                gap_index += 1;
+            }
+            line.push_str(piece.text);
+        }
+        line
+    }
+}
+
+/// 制表符已在渲染前替换为空格，这里只需识别空格
+fn is_blank(grapheme: &str) -> bool {
+    grapheme == " "
+}
+
+/// 两个相邻字素簇之间能否断行：宽字符（CJK、emoji）前后均可断开，但标点不能落在行首或独留行尾
+fn can_break(prev: &str, next: &str) -> bool {
+    let wide = grapheme_width(prev) >= 2 || grapheme_width(next) >= 2;
+    wide && !NO_START.contains(next) && !NO_END.contains(prev)
+}
+
+/// 把一段（不含换行）拆成不可拆分的片段
+fn pieces(paragraph: &str, wrap: Wrap) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut gap_start = 0;
+    let mut text_start = 0;
+    let mut prev: Option<&str> = None;
+    for (i, grapheme) in paragraph.grapheme_indices(true) {
+        if wrap == Wrap::Word && is_blank(grapheme) {
+            if text_start < i {
+                pieces.push(Piece {
+                    gap: &paragraph[gap_start..text_start],
+                    text: &paragraph[text_start..i],
+                });
+                gap_start = i;
+            }
+            text_start = i + grapheme.len();
+            prev = None;
+            continue;
+        }
+        let boundary = match (wrap, prev) {
+            (Wrap::Char, Some(_)) => true,
+            (Wrap::Word, Some(prev)) => can_break(prev, grapheme),
+            (_, None) => false,
+        };
+        if boundary {
+            pieces.push(Piece {
+                gap: &paragraph[gap_start..text_start],
+                text: &paragraph[text_start..i],
+            });
+            gap_start = i;
+            text_start = i;
+        }
+        prev = Some(grapheme);
+    }
+    if text_start < paragraph.len() {
+        pieces.push(Piece {
+            gap: &paragraph[gap_start..text_start],
+            text: &paragraph[text_start..],
+        });
+    }
+    pieces
+}
+
+/// 把一段折成宽度不超过 `max_width` 的若干行；单个片段超宽时按字素簇硬折行，
+/// 每行至少放入一个字素簇以保证前进，因此只有单个字素簇比 `max_width` 更宽时才会超出
+pub(crate) fn wrap(paragraph: &str, max_width: usize, wrap: Wrap) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    let mut line = Line::default();
+    let mut line_width = 0;
+    for piece in pieces(paragraph, wrap) {
+        let gap = if line.pieces.is_empty() && !lines.is_empty() {
+            ""
+        } else {
+            piece.gap
+        };
+        let piece_width = visual_width(piece.text);
+        if line_width + gap.len() + piece_width <= max_width {
+            line_width += gap.len() + piece_width;
+            line.pieces.push(Piece { gap, text: piece.text });
+            continue;
+        }
+        if !line.pieces.is_empty() {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        if piece_width <= max_width {
+            line_width = piece_width;
+            line.pieces.push(Piece { gap: "", text: piece.text });
+            continue;
+        }
+        // 超长的词硬折行
+        let mut start = 0;
+        for (i, grapheme) in piece.text.grapheme_indices(true) {
+            let grapheme_width = grapheme_width(grapheme);
+            if line_width + grapheme_width > max_width && i > start {
+                lines.push(Line {
+                    pieces: vec![Piece {
+                        gap: "",
+                        text: &piece.text[start..i],
+                    }],
+                });
+                start = i;
+                line_width = 0;
+            }
+            line_width += grapheme_width;
+        }
+        line.pieces.push(Piece {
+            gap: "",
+            text: &piece.text[start..],
+        });
+    }
+    if !line.pieces.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrapped(paragraph: &str, max_width: usize, mode: Wrap) -> Vec<String> {
+        wrap(paragraph, max_width, mode).iter().map(Line::text).collect()
+    }
+
+    #[test]
+    fn test_wrap_words_and_cjk() {
+        assert_eq!(
+            vec!["The quick", "brown fox", "jumps"],
+            wrapped("The quick brown fox jumps", 10, Wrap::Word)
+        );
+        assert_eq!(
+            vec!["supercalif", "ragilistic", "ok"],
+            wrapped("supercalifragilistic   ok", 10, Wrap::Word)
+        );
+        assert_eq!(
+            vec!["中文可以逐", "字断开，", "（标点）不", "在行首。"],
+            wrapped("中文可以逐字断开，（标点）不在行首。", 10, Wrap::Word)
+        );
+        assert_eq!(vec!["一二三四", "五，六"], wrapped("一二三四五，六", 10, Wrap::Word));
+        assert_eq!(
+            vec!["Rush 工具", "支持emoji", "🎉，很好"],
+            wrapped("Rush 工具支持emoji🎉，很好", 10, Wrap::Word)
+        );
+        assert_eq!(
+            vec!["The quick ", "brown fox ", "jumps"],
+            wrapped("The quick brown fox jumps", 10, Wrap::Char)
+        );
+    }
+
+    #[test]
+    fn test_align() {
+        let line = &wrap("a bb ccc", 20, Wrap::Word)[0];
+        assert_eq!("a bb ccc    ", line.render(Align::Left, 12, false));
+        assert_eq!("    a bb ccc", line.render(Align::Right, 12, false));
+        assert_eq!("  a bb ccc  ", line.render(Align::Center, 12, false));
+        assert_eq!("a   bb   ccc", line.render(Align::Justify, 12, false));
+        assert_eq!("a    bb   ccc", line.render(Align::Justify, 13, false));
+        assert_eq!("a bb ccc    ", line.render(Align::Justify, 12, true));
+    }
+}
@@ -0,0 +1,74 @@
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use rush_var::{expand_env, expand_env_recursive, expand_into};
+use std::collections::HashMap;
+
+fn env() -> HashMap<String, String> {
+    (0..64)
+        .map(|i| (format!("VAR_{i}"), format!("/opt/value-{i}/bin")))
+        .chain([
+            ("HOME".to_string(), "/home/rush".to_string()),
+            ("NESTED".to_string(), "${HOME}/.local/${VAR_1}".to_string()),
+        ])
+        .collect()
+}
+
+/// 模拟生成的 shell 配置：多数行是普通文本，部分行引用变量
+fn config(lines: usize) -> String {
+    (0..lines)
+        .map(|i| match i % 4 {
+            0 => format!("export PATH=\"$VAR_{}:${{PATH:-/usr/bin}}\"\n", i % 64),
+            1 => format!("alias l{i}='ls -la ${{HOME}}/projects/{i}'\n"),
+            _ => format!("# plain comment line number {i} without any substitution\n"),
+        })
+        .collect()
+}
+
+fn plain(lines: usize) -> String {
+    (0..lines).map(|i| format!("plain line {i} with nothing to expand\n")).collect()
+}
+
+fn bench_expand(c: &mut Criterion) {
+    let env = env();
+    let mut group = c.benchmark_group("expand_env");
+    for lines in [16, 256, 4096] {
+        let input = config(lines);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("mixed", lines), &input, |b, input| {
+            b.iter(|| expand_env(black_box(input), &env))
+        });
+        let input = plain(lines);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("plain", lines), &input, |b, input| {
+            b.iter(|| expand_env(black_box(input), &env))
+        });
+    }
+    group.finish();
+}
+
+fn bench_expand_into(c: &mut Criterion) {
+    let env = env();
+    let input = config(4096);
+    let mut group = c.benchmark_group("expand_into");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("reused_buffer", |b| {
+        let mut out = String::with_capacity(input.len() * 2);
+        b.iter(|| {
+            out.clear();
+            expand_into(black_box(&input), &env, &mut out).unwrap();
+        })
+    });
+    group.bench_function("fresh_string", |b| b.iter(|| expand_env(black_box(&input), &env).into_owned()));
+    group.finish();
+}
+
+fn bench_recursive(c: &mut Criterion) {
+    let env = env();
+    let input = "$NESTED:".repeat(1024);
+    let mut group = c.benchmark_group("expand_env_recursive");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("nested", |b| b.iter(|| expand_env_recursive(black_box(&input), &env)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_expand, bench_expand_into, bench_recursive);
+criterion_main!(benches);
@@ -0,0 +1,913 @@
+//! 插值文本的语法树。
+//!
+//! [`parse`] 把输入解析为 [`Node`] 序列：字面文本、变量引用（含操作符与操作数）、波浪号与命令替换。
+//! `expand_env` 等函数只是在语法树上求值；需要分析依赖时可以直接遍历语法树，或使用 [`referenced_vars`]。
+//!
+//! ```rust
+//! use rush_var::ast::{Node, Operator, parse};
+//! let nodes = parse("${FOO:-/usr}/bin").unwrap();
+//! assert_eq!(
+//!     nodes,
+//!     vec![
+//!         Node::Var {
+//!             name: "FOO",
+//!             op: Some(Operator::Default { colon: true, word: vec![Node::Literal("/usr")] }),
+//!             span: 0..12,
+//!         },
+//!         Node::Literal("/bin"),
+//!     ]
+//! );
+//! ```
+
+use crate::error::ExpandError;
+use std::ops::Range;
+
+/// 语法树节点，借用输入中的切片，`span` 为节点在输入中的字节区间
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node<'a> {
+    /// 字面文本，转义已按所在位置处理
+    Literal(&'a str),
+    /// `$VAR`、`${VAR}` 或带操作符的 `${VAR op word}`
+    Var {
+        name: &'a str,
+        op: Option<Operator<'a>>,
+        span: Range<usize>,
+    },
+    /// `${#VAR}`
+    Length { name: &'a str, span: Range<usize> },
+    /// 词首或 `:` 之后的 `~`、`~user`、`~+`、`~-`，`prefix` 为 `~` 之后的部分
+    Tilde { prefix: &'a str, span: Range<usize> },
+    /// `$(command)` 或反引号，`command` 为未展开的命令文本
+    Command {
+        command: &'a str,
+        backtick: bool,
+        span: Range<usize>,
+    },
+    /// zsh 的 `${(U)VAR}` 等参数标志，作用于内层节点的展开结果，仅在 [`Dialect::Zsh`] 下解析
+    Flagged { flags: Vec<Flag>, node: Box<Node<'a>> },
+    /// 宽松模式下无法识别的 `${...}`，求值为空串
+    Invalid { span: Range<usize> },
+}
+
+/// `${VAR op word}` 中的操作符，操作数已解析为节点序列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operator<'a> {
+    /// `${VAR:-word}` / `${VAR-word}`
+    Default { colon: bool, word: Vec<Node<'a>> },
+    /// `${VAR:=word}` / `${VAR=word}`
+    Assign { colon: bool, word: Vec<Node<'a>> },
+    /// `${VAR:?word}` / `${VAR?word}`
+    Error { colon: bool, word: Vec<Node<'a>> },
+    /// `${VAR:+word}` / `${VAR+word}`
+    Alternative { colon: bool, word: Vec<Node<'a>> },
+    /// `${VAR#pattern}` / `${VAR##pattern}`
+    RemovePrefix { longest: bool, pattern: Vec<Node<'a>> },
+    /// `${VAR%pattern}` / `${VAR%%pattern}`
+    RemoveSuffix { longest: bool, pattern: Vec<Node<'a>> },
+    /// `${VAR/pattern/replacement}` 及 `//`、`/#`、`/%` 变体
+    Replace {
+        all: bool,
+        anchor: Option<Anchor>,
+        pattern: Vec<Node<'a>>,
+        replacement: Vec<Node<'a>>,
+    },
+    /// `${VAR:offset}` / `${VAR:offset:length}`
+    Substring {
+        offset: Vec<Node<'a>>,
+        length: Option<Vec<Node<'a>>>,
+    },
+    /// zsh 的 `${VAR:h}`、`${VAR:t:r}` 等修饰符，按顺序作用于值，仅在 [`Dialect::Zsh`] 下解析
+    Modifiers(Vec<Modifier>),
+}
+
+/// 解析所遵循的 shell 方言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    /// Bash 的参数展开
+    #[default]
+    Bash,
+    /// 在 Bash 的基础上支持 zsh 的修饰符 `${VAR:h}` 与参数标志 `${(U)VAR}`
+    Zsh,
+}
+
+/// 识别的变量语法，可以用 `|` 组合，默认只有 [`Syntax::DOLLAR`]。
+///
+/// 其他语法只在顶层文本（及递归展开的变量值）中识别，解析为与 `$VAR` 相同的 [`Node::Var`]，
+/// 因此严格模式、递归展开与各种 [`crate::env_source::EnvSource`] 对它们同样适用。
+///
+/// ```rust
+/// use rush_var::ast::Syntax;
+/// use rush_var::{ExpandOptions, expand_env_with};
+/// let env = [("USER", "alice")];
+/// let options = ExpandOptions { syntax: Syntax::BATCH | Syntax::MUSTACHE, ..Default::default() };
+/// assert_eq!(expand_env_with("%USER% {{ USER }} 100%% $USER \\{{ USER }}", &env, &options), "alice alice 100% $USER {{ USER }}");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Syntax(u8);
+
+impl Syntax {
+    /// Shell 的 `$VAR`、`${VAR...}`、`$(...)` 与反引号，`$$` 为字面 `$`
+    pub const DOLLAR: Syntax = Syntax(1);
+    /// Windows 批处理的 `%VAR%`，`%%` 为字面 `%`
+    pub const BATCH: Syntax = Syntax(1 << 1);
+    /// PowerShell 的 `$env:VAR`（`env` 不区分大小写）；未启用 [`Syntax::DOLLAR`] 时 `` `$ `` 为字面 `$`
+    pub const POWERSHELL: Syntax = Syntax(1 << 2);
+    /// Mustache 风格的 `{{ VAR }}`，括号内可有空白，`\{{` 为字面 `{{`
+    pub const MUSTACHE: Syntax = Syntax(1 << 3);
+
+    pub fn contains(self, other: Syntax) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        Syntax::DOLLAR
+    }
+}
+
+impl std::ops::BitOr for Syntax {
+    type Output = Syntax;
+
+    fn bitor(self, rhs: Syntax) -> Syntax {
+        Syntax(self.0 | rhs.0)
+    }
+}
+
+/// zsh 的路径与大小写修饰符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    /// `:h`，去掉最后一级路径
+    Head,
+    /// `:t`，只保留最后一级路径
+    Tail,
+    /// `:r`，去掉扩展名
+    Root,
+    /// `:e`，只保留扩展名
+    Extension,
+    /// `:a`，转为绝对路径并化简 `.` 与 `..`
+    Absolute,
+    /// `:A`，同 `:a`，并解析符号链接
+    Resolve,
+    /// `:u`，转为大写
+    Upper,
+    /// `:l`，转为小写
+    Lower,
+}
+
+/// zsh 的参数标志
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    /// `(U)`，转为大写
+    Upper,
+    /// `(L)`，转为小写
+    Lower,
+    /// `(C)`，每个单词首字母大写
+    Capitalize,
+}
+
+impl Modifier {
+    fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            'h' => Modifier::Head,
+            't' => Modifier::Tail,
+            'r' => Modifier::Root,
+            'e' => Modifier::Extension,
+            'a' => Modifier::Absolute,
+            'A' => Modifier::Resolve,
+            'u' => Modifier::Upper,
+            'l' => Modifier::Lower,
+            _ => return None,
+        })
+    }
+
+    /// `rest` 整体为 `:h:t` 形式的修饰符序列时解析，否则返回 None 交给 Bash 操作符处理
+    fn parse_all(rest: &str) -> Option<Vec<Self>> {
+        let mut modifiers = Vec::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            if c != ':' {
+                return None;
+            }
+            modifiers.push(Self::from_char(chars.next()?)?);
+        }
+        (!modifiers.is_empty()).then_some(modifiers)
+    }
+}
+
+impl Flag {
+    fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            'U' => Flag::Upper,
+            'L' => Flag::Lower,
+            'C' => Flag::Capitalize,
+            _ => return None,
+        })
+    }
+}
+
+/// `${VAR/#pattern/rep}` 与 `${VAR/%pattern/rep}` 的锚定位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Start,
+    End,
+}
+
+/// 操作符的种类，与操作数分开以便查表
+#[derive(Debug, Clone, Copy)]
+enum OpKind {
+    Default { colon: bool },
+    Assign { colon: bool },
+    Error { colon: bool },
+    Alternative { colon: bool },
+    RemovePrefix { longest: bool },
+    RemoveSuffix { longest: bool },
+    Replace { all: bool, anchor: Option<Anchor> },
+    Substring,
+}
+
+/// 文本中 `\` 的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quoting {
+    /// 顶层文本，`\` 原样保留
+    Raw,
+    /// `${VAR:-w}` 等操作数，`\x` 去掉反斜杠
+    Word,
+    /// 通配符模式，保留 `\` 交给模式匹配作为转义
+    Pattern,
+}
+
+/// 按 Bash 语法解析输入，语法错误时返回带位置的 [`ExpandError`]
+pub fn parse(input: &str) -> Result<Vec<Node<'_>>, ExpandError> {
+    parse_dialect(input, Dialect::Bash)
+}
+
+/// 按指定方言解析输入
+///
+/// ```rust
+/// use rush_var::ast::{Dialect, Flag, Modifier, Node, Operator, parse_dialect};
+/// let nodes = parse_dialect("${(U)FILE:t:r}", Dialect::Zsh).unwrap();
+/// let var = Node::Var { name: "FILE", op: Some(Operator::Modifiers(vec![Modifier::Tail, Modifier::Root])), span: 0..14 };
+/// assert_eq!(nodes, vec![Node::Flagged { flags: vec![Flag::Upper], node: Box::new(var) }]);
+/// ```
+pub fn parse_dialect(input: &str, dialect: Dialect) -> Result<Vec<Node<'_>>, ExpandError> {
+    parse_syntax(input, dialect, Syntax::DOLLAR)
+}
+
+/// 按指定方言与变量语法解析输入
+///
+/// ```rust
+/// use rush_var::ast::{Dialect, Node, Syntax, parse_syntax};
+/// let nodes = parse_syntax("%PATH%;$env:Path", Dialect::Bash, Syntax::BATCH | Syntax::POWERSHELL).unwrap();
+/// assert_eq!(
+///     nodes,
+///     vec![
+///         Node::Var { name: "PATH", op: None, span: 0..6 },
+///         Node::Literal(";"),
+///         Node::Var { name: "Path", op: None, span: 7..16 },
+///     ]
+/// );
+/// ```
+pub fn parse_syntax(input: &str, dialect: Dialect, syntax: Syntax) -> Result<Vec<Node<'_>>, ExpandError> {
+    parse_with(input, 0, true, dialect, syntax)
+}
+
+/// 按宽松规则解析，从不失败：未闭合的 `${` 视为在末尾闭合，未闭合的命令替换保留为字面文本
+pub(crate) fn parse_lenient(input: &str, dialect: Dialect, syntax: Syntax) -> Vec<Node<'_>> {
+    parse_with(input, 0, false, dialect, syntax).unwrap_or_else(|_| unreachable!("宽松模式下解析不会失败"))
+}
+
+pub(crate) fn parse_with(input: &str, base: usize, fallible: bool, dialect: Dialect, syntax: Syntax) -> Result<Vec<Node<'_>>, ExpandError> {
+    Parser { fallible, dialect, syntax }.parse_text(input, base, Quoting::Raw)
+}
+
+/// 输入引用的全部变量名（按首次出现的顺序去重），包括操作数与命令替换中的引用。
+/// 按 zsh 语法解析，`${(U)VAR}` 中的引用同样会被收集
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::ast::referenced_vars;
+/// assert_eq!(
+///     referenced_vars("$(java_home -v ${JAVA_VERSION})/bin:${PATH:-$DEFAULT_PATH}:$PATH"),
+///     vec!["JAVA_VERSION", "PATH", "DEFAULT_PATH"]
+/// );
+/// ```
+pub fn referenced_vars(input: &str) -> Vec<&str> {
+    referenced_vars_dialect(input, Dialect::Zsh)
+}
+
+/// 同 [`referenced_vars`]，按指定方言解析
+///
+/// ```rust
+/// use rush_var::ast::{Dialect, referenced_vars_dialect};
+/// assert_eq!(referenced_vars_dialect("${(U)NAME} $HOME", Dialect::Zsh), vec!["NAME", "HOME"]);
+/// assert_eq!(referenced_vars_dialect("${(U)NAME} $HOME", Dialect::Bash), vec!["HOME"]);
+/// ```
+pub fn referenced_vars_dialect(input: &str, dialect: Dialect) -> Vec<&str> {
+    fn collect<'a>(nodes: &[Node<'a>], dialect: Dialect, vars: &mut Vec<&'a str>) {
+        for node in nodes {
+            match node {
+                Node::Var { name, op, .. } => {
+                    if !name.is_empty() && !vars.contains(name) {
+                        vars.push(name);
+                    }
+                    if let Some(op) = op {
+                        for word in op.words() {
+                            collect(word, dialect, vars);
+                        }
+                    }
+                }
+                Node::Length { name, .. } if !vars.contains(name) => vars.push(name),
+                Node::Command { command, .. } => collect(&parse_lenient(command, dialect, Syntax::DOLLAR), dialect, vars),
+                Node::Flagged { node, .. } => collect(std::slice::from_ref(node), dialect, vars),
+                _ => {}
+            }
+        }
+    }
+    let mut vars = Vec::new();
+    collect(&parse_lenient(input, dialect, Syntax::DOLLAR), dialect, &mut vars);
+    vars
+}
+
+impl<'a> Operator<'a> {
+    /// 操作符的全部操作数
+    pub fn words(&self) -> Vec<&[Node<'a>]> {
+        match self {
+            Operator::Default { word, .. }
+            | Operator::Assign { word, .. }
+            | Operator::Error { word, .. }
+            | Operator::Alternative { word, .. } => vec![word],
+            Operator::RemovePrefix { pattern, .. } | Operator::RemoveSuffix { pattern, .. } => vec![pattern],
+            Operator::Replace { pattern, replacement, .. } => vec![pattern, replacement],
+            Operator::Substring { offset, length } => [Some(offset), length.as_ref()].into_iter().flatten().map(Vec::as_slice).collect(),
+            Operator::Modifiers(_) => Vec::new(),
+        }
+    }
+}
+
+fn is_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// `i` 是否位于词首或路径列表中 `:` 之后
+fn is_word_start(input: &str, i: usize) -> bool {
+    input[..i]
+        .chars()
+        .next_back()
+        .is_none_or(|prev| prev == ':' || prev.is_whitespace())
+}
+
+struct Parser {
+    fallible: bool,
+    dialect: Dialect,
+    syntax: Syntax,
+}
+
+/// 收集节点，在输入中相邻的字面片段合并为一个切片，避免为字面文本分配内存
+struct Nodes<'a> {
+    input: &'a str,
+    nodes: Vec<Node<'a>>,
+    literal: Option<Range<usize>>,
+}
+
+impl<'a> Nodes<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            nodes: Vec::new(),
+            literal: None,
+        }
+    }
+
+    fn literal(&mut self, range: Range<usize>) {
+        match &mut self.literal {
+            Some(literal) if literal.end == range.start => literal.end = range.end,
+            _ => {
+                self.flush();
+                self.literal = Some(range);
+            }
+        }
+    }
+
+    fn push(&mut self, node: Node<'a>) {
+        self.flush();
+        self.nodes.push(node);
+    }
+
+    fn flush(&mut self) {
+        if let Some(literal) = self.literal.take()
+            && !literal.is_empty()
+        {
+            self.nodes.push(Node::Literal(&self.input[literal]));
+        }
+    }
+
+    fn finish(mut self) -> Vec<Node<'a>> {
+        self.flush();
+        self.nodes
+    }
+}
+
+impl Parser {
+    /// `base` 为 `input` 在最外层输入中的字节偏移，用于计算节点位置
+    fn parse_text<'a>(&self, input: &'a str, base: usize, quoting: Quoting) -> Result<Vec<Node<'a>>, ExpandError> {
+        let mut nodes = Nodes::new(input);
+        let mut i = 0;
+        while let Some(c) = input[i..].chars().next() {
+            match c {
+                '%' if self.accepts(Syntax::BATCH, quoting) => {
+                    let rest = &input[i + 1..];
+                    let name_len = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+                    if rest.starts_with('%') {
+                        nodes.literal(i..i + 1);
+                        i += 2;
+                    } else if name_len > 0 && rest[name_len..].starts_with('%') {
+                        let end = i + name_len + 2;
+                        nodes.push(Node::Var {
+                            name: &rest[..name_len],
+                            op: None,
+                            span: base + i..base + end,
+                        });
+                        i = end;
+                    } else {
+                        nodes.literal(i..i + 1);
+                        i += 1;
+                    }
+                }
+                '$' if self.accepts(Syntax::POWERSHELL, quoting)
+                    && input.get(i + 1..i + 5).is_some_and(|prefix| prefix.eq_ignore_ascii_case("env:"))
+                    && input[i + 5..].starts_with(is_name_char) =>
+                {
+                    let start = i + 5;
+                    let end = input[start..].find(|c: char| !is_name_char(c)).map_or(input.len(), |n| start + n);
+                    nodes.push(Node::Var {
+                        name: &input[start..end],
+                        op: None,
+                        span: base + i..base + end,
+                    });
+                    i = end;
+                }
+                '{' if self.accepts(Syntax::MUSTACHE, quoting) && input[i..].starts_with("{{") => {
+                    let var = input[i + 2..].find("}}").and_then(|close| {
+                        let name = input[i + 2..i + 2 + close].trim();
+                        let valid = !name.is_empty() && name.chars().all(is_name_char);
+                        valid.then_some((name, i + close + 4))
+                    });
+                    match var {
+                        Some((name, end)) => {
+                            nodes.push(Node::Var {
+                                name,
+                                op: None,
+                                span: base + i..base + end,
+                            });
+                            i = end;
+                        }
+                        None => {
+                            nodes.literal(i..i + 2);
+                            i += 2;
+                        }
+                    }
+                }
+                '\\' if self.accepts(Syntax::MUSTACHE, quoting) && input[i + 1..].starts_with("{{") => {
+                    nodes.literal(i + 1..i + 3);
+                    i += 3;
+                }
+                '`' if self.accepts(Syntax::POWERSHELL, quoting)
+                    && !self.syntax.contains(Syntax::DOLLAR)
+                    && input[i + 1..].starts_with('$') =>
+                {
+                    nodes.literal(i + 1..i + 2);
+                    i += 2;
+                }
+                '$' | '`' if !self.syntax.contains(Syntax::DOLLAR) => {
+                    nodes.literal(i..i + 1);
+                    i += 1;
+                }
+                '\\' if quoting != Quoting::Raw => {
+                    let escaped = input[i + 1..].chars().next().map_or(0, char::len_utf8);
+                    match (quoting, escaped) {
+                        // 保留反斜杠，交给模式匹配处理
+                        (Quoting::Pattern, _) => nodes.literal(i..i + 1 + escaped),
+                        // 末尾单独的反斜杠原样保留
+                        (_, 0) => nodes.literal(i..i + 1),
+                        _ => nodes.literal(i + 1..i + 1 + escaped),
+                    }
+                    i += 1 + escaped;
+                }
+                '~' if quoting != Quoting::Pattern && is_word_start(input, i) => {
+                    let prefix = input[i + 1..]
+                        .split(|c: char| c == '/' || c == ':' || c.is_whitespace())
+                        .next()
+                        .unwrap_or_default();
+                    let valid = matches!(prefix, "+" | "-") || prefix.chars().all(|c| is_name_char(c) || c == '-' || c == '.');
+                    if valid {
+                        let end = i + 1 + prefix.len();
+                        nodes.push(Node::Tilde {
+                            prefix,
+                            span: base + i..base + end,
+                        });
+                        i = end;
+                    } else {
+                        nodes.literal(i..i + 1);
+                        i += 1;
+                    }
+                }
+                '`' => {
+                    let Some(close) = find_unescaped(&input[i + 1..], '`').map(|n| i + 1 + n) else {
+                        self.unterminated_substitution(base + i..base + input.len())?;
+                        nodes.literal(i..input.len());
+                        break;
+                    };
+                    nodes.push(Node::Command {
+                        command: &input[i + 1..close],
+                        backtick: true,
+                        span: base + i..base + close + 1,
+                    });
+                    i = close + 1;
+                }
+                '$' => match input[i + 1..].chars().next() {
+                    Some('(') => {
+                        let Some(close) = find_closing_paren(input, i + 2) else {
+                            self.unterminated_substitution(base + i..base + input.len())?;
+                            nodes.literal(i..input.len());
+                            break;
+                        };
+                        if input[i..].starts_with("$((") {
+                            // 算术展开交给 shell
+                            nodes.literal(i..close + 1);
+                        } else {
+                            nodes.push(Node::Command {
+                                command: &input[i + 2..close],
+                                backtick: false,
+                                span: base + i..base + close + 1,
+                            });
+                        }
+                        i = close + 1;
+                    }
+                    Some('$') => {
+                        nodes.literal(i + 1..i + 2);
+                        i += 2;
+                    }
+                    Some('{') => {
+                        let start = i + 2;
+                        let (body, next) = match find_closing_brace(input, start) {
+                            Some(close) => (&input[start..close], close + 1),
+                            None => {
+                                let error = ExpandError::UnterminatedBrace {
+                                    span: base + i..base + input.len(),
+                                };
+                                if self.fallible {
+                                    return Err(error);
+                                }
+                                (&input[start..], input.len())
+                            }
+                        };
+                        nodes.push(self.parse_param(body, base + start, base + i..base + next)?);
+                        i = next;
+                    }
+                    Some(ch) if is_name_char(ch) => {
+                        let start = i + 1;
+                        let end = input[start..].find(|c: char| !is_name_char(c)).map_or(input.len(), |n| start + n);
+                        nodes.push(Node::Var {
+                            name: &input[start..end],
+                            op: None,
+                            span: base + i..base + end,
+                        });
+                        i = end;
+                    }
+                    _ => {
+                        nodes.literal(i..i + 1);
+                        i += 1;
+                    }
+                },
+                c => {
+                    nodes.literal(i..i + c.len_utf8());
+                    i += c.len_utf8();
+                }
+            }
+        }
+        Ok(nodes.finish())
+    }
+
+    /// 是否在当前位置识别 `syntax`，[`Syntax::DOLLAR`] 以外的语法只在顶层文本中识别
+    fn accepts(&self, syntax: Syntax, quoting: Quoting) -> bool {
+        quoting == Quoting::Raw && self.syntax.contains(syntax)
+    }
+
+    fn bad_substitution<'a>(&self, span: Range<usize>) -> Result<Node<'a>, ExpandError> {
+        if self.fallible {
+            return Err(ExpandError::BadSubstitution { span });
+        }
+        Ok(Node::Invalid { span })
+    }
+
+    fn unterminated_substitution(&self, span: Range<usize>) -> Result<(), ExpandError> {
+        if self.fallible {
+            return Err(ExpandError::UnterminatedSubstitution { span });
+        }
+        Ok(())
+    }
+
+    /// 解析 `${` 与 `}` 之间的内容，`base` 为 `body` 的偏移，`span` 为整个 `${...}` 的区间
+    fn parse_param<'a>(&self, body: &'a str, base: usize, span: Range<usize>) -> Result<Node<'a>, ExpandError> {
+        if self.dialect == Dialect::Zsh
+            && let Some(flagged) = body.strip_prefix('(')
+        {
+            let flags = flagged
+                .split_once(')')
+                .and_then(|(flags, _)| flags.chars().map(Flag::from_char).collect::<Option<Vec<_>>>());
+            let Some(flags) = flags.filter(|flags| !flags.is_empty()) else {
+                return self.bad_substitution(span);
+            };
+            // 每个标志占一个字节
+            let inner = &flagged[flags.len() + 1..];
+            let node = self.parse_param(inner, base + flags.len() + 2, span)?;
+            return Ok(Node::Flagged {
+                flags,
+                node: Box::new(node),
+            });
+        }
+        if let Some(name) = body.strip_prefix('#')
+            && !name.is_empty()
+            && name.chars().all(is_name_char)
+        {
+            return Ok(Node::Length { name, span });
+        }
+        let name_len = body.find(|c: char| !is_name_char(c)).unwrap_or(body.len());
+        let (name, rest) = body.split_at(name_len);
+        if rest.is_empty() {
+            return Ok(Node::Var { name, op: None, span });
+        }
+        if self.dialect == Dialect::Zsh
+            && !name.is_empty()
+            && let Some(modifiers) = Modifier::parse_all(rest)
+        {
+            return Ok(Node::Var {
+                name,
+                op: Some(Operator::Modifiers(modifiers)),
+                span,
+            });
+        }
+        let Some((kind, word)) = parse_op(rest).filter(|_| !name.is_empty()) else {
+            return self.bad_substitution(span);
+        };
+        // 操作数位于 body 末尾
+        let word_base = base + body.len() - word.len();
+        let parse_word = |text: &'a str, base: usize| self.parse_text(text, base, Quoting::Word);
+        let parse_pattern = |text: &'a str, base: usize| self.parse_text(text, base, Quoting::Pattern);
+        let op = match kind {
+            OpKind::Default { colon } => Operator::Default {
+                colon,
+                word: parse_word(word, word_base)?,
+            },
+            OpKind::Assign { colon } => Operator::Assign {
+                colon,
+                word: parse_word(word, word_base)?,
+            },
+            OpKind::Error { colon } => Operator::Error {
+                colon,
+                word: parse_word(word, word_base)?,
+            },
+            OpKind::Alternative { colon } => Operator::Alternative {
+                colon,
+                word: parse_word(word, word_base)?,
+            },
+            OpKind::RemovePrefix { longest } => Operator::RemovePrefix {
+                longest,
+                pattern: parse_pattern(word, word_base)?,
+            },
+            OpKind::RemoveSuffix { longest } => Operator::RemoveSuffix {
+                longest,
+                pattern: parse_pattern(word, word_base)?,
+            },
+            OpKind::Replace { all, anchor } => {
+                let (pattern, replacement) = match find_unescaped(word, '/') {
+                    Some(slash) => (&word[..slash], &word[slash + 1..]),
+                    None => (word, ""),
+                };
+                let replacement_base = word_base + word.len() - replacement.len();
+                Operator::Replace {
+                    all,
+                    anchor,
+                    pattern: parse_pattern(pattern, word_base)?,
+                    replacement: parse_word(replacement, replacement_base)?,
+                }
+            }
+            OpKind::Substring => {
+                let (offset, length) = match find_unescaped(word, ':') {
+                    Some(colon) => (&word[..colon], Some(&word[colon + 1..])),
+                    None => (word, None),
+                };
+                let length = match length {
+                    Some(length) => Some(parse_word(length, word_base + word.len() - length.len())?),
+                    None => None,
+                };
+                Operator::Substring {
+                    offset: parse_word(offset, word_base)?,
+                    length,
+                }
+            }
+        };
+        Ok(Node::Var { name, op: Some(op), span })
+    }
+}
+
+/// 解析变量名之后的操作符，返回操作符与其操作数
+fn parse_op(rest: &str) -> Option<(OpKind, &str)> {
+    const OPS: &[(&str, OpKind)] = &[
+        (":-", OpKind::Default { colon: true }),
+        (":=", OpKind::Assign { colon: true }),
+        (":?", OpKind::Error { colon: true }),
+        (":+", OpKind::Alternative { colon: true }),
+        ("-", OpKind::Default { colon: false }),
+        ("=", OpKind::Assign { colon: false }),
+        ("?", OpKind::Error { colon: false }),
+        ("+", OpKind::Alternative { colon: false }),
+        ("##", OpKind::RemovePrefix { longest: true }),
+        ("#", OpKind::RemovePrefix { longest: false }),
+        ("%%", OpKind::RemoveSuffix { longest: true }),
+        ("%", OpKind::RemoveSuffix { longest: false }),
+        ("//", OpKind::Replace { all: true, anchor: None }),
+        (
+            "/#",
+            OpKind::Replace {
+                all: false,
+                anchor: Some(Anchor::Start),
+            },
+        ),
+        (
+            "/%",
+            OpKind::Replace {
+                all: false,
+                anchor: Some(Anchor::End),
+            },
+        ),
+        ("/", OpKind::Replace { all: false, anchor: None }),
+        (":", OpKind::Substring),
+    ];
+    OPS.iter()
+        .find_map(|(prefix, op)| rest.strip_prefix(prefix).map(|word| (*op, word)))
+}
+
+/// 从 `start` 开始查找与 `${` 配对的 `}`，跳过转义与嵌套的 `${...}`
+fn find_closing_brace(input: &str, start: usize) -> Option<usize> {
+    let mut depth = 1;
+    let mut chars = input[start..].char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '$' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                chars.next();
+                depth += 1;
+            }
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start + offset);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 从 `start` 开始查找与 `$(` 配对的 `)`，跳过转义、引号与嵌套的括号
+fn find_closing_paren(input: &str, start: usize) -> Option<usize> {
+    let mut depth = 1;
+    let mut quote = None;
+    let mut chars = input[start..].char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => {
+                chars.next();
+            }
+            (Some('"'), '"') => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start + offset);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 查找第一个不在转义或嵌套 `${...}` 中的 `target`
+fn find_unescaped(word: &str, target: char) -> Option<usize> {
+    let mut chars = word.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '$' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                let close = find_closing_brace(word, offset + 2)?;
+                while chars.peek().is_some_and(|(offset, _)| *offset <= close) {
+                    chars.next();
+                }
+            }
+            c if c == target => return Some(offset),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nodes() {
+        let nodes = parse(r"~/bin:$A${B#\*}`date`$((1+1))").unwrap();
+        assert_eq!(
+            nodes,
+            vec![
+                Node::Tilde { prefix: "", span: 0..1 },
+                Node::Literal("/bin:"),
+                Node::Var {
+                    name: "A",
+                    op: None,
+                    span: 6..8
+                },
+                Node::Var {
+                    name: "B",
+                    op: Some(Operator::RemovePrefix {
+                        longest: false,
+                        pattern: vec![Node::Literal(r"\*")]
+                    }),
+                    span: 8..15
+                },
+                Node::Command {
+                    command: "date",
+                    backtick: true,
+                    span: 15..21
+                },
+                Node::Literal("$((1+1))"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("a ${A B}"), Err(ExpandError::BadSubstitution { span: 2..8 }));
+        assert_eq!(parse("${A:-${B}"), Err(ExpandError::UnterminatedBrace { span: 0..9 }));
+        assert_eq!(parse("$(echo"), Err(ExpandError::UnterminatedSubstitution { span: 0..6 }));
+        assert_eq!(
+            parse_lenient("a ${A B}", Dialect::Bash, Syntax::DOLLAR),
+            vec![Node::Literal("a "), Node::Invalid { span: 2..8 }]
+        );
+        assert_eq!(parse("${(U)A}"), Err(ExpandError::BadSubstitution { span: 0..7 }));
+        assert_eq!(
+            parse_dialect("${(X)A}", Dialect::Zsh),
+            Err(ExpandError::BadSubstitution { span: 0..7 })
+        );
+    }
+
+    #[test]
+    fn test_parse_zsh() {
+        let modifiers = |modifiers| Some(Operator::Modifiers(modifiers));
+        assert_eq!(
+            parse_dialect("${A:h}${B:1}${C:-x}", Dialect::Zsh).unwrap()[..2],
+            [
+                Node::Var {
+                    name: "A",
+                    op: modifiers(vec![Modifier::Head]),
+                    span: 0..6
+                },
+                Node::Var {
+                    name: "B",
+                    op: Some(Operator::Substring {
+                        offset: vec![Node::Literal("1")],
+                        length: None
+                    }),
+                    span: 6..12
+                },
+            ]
+        );
+        assert_eq!(
+            parse_dialect("${(LX)A}", Dialect::Zsh),
+            Err(ExpandError::BadSubstitution { span: 0..8 })
+        );
+        assert_eq!(referenced_vars("${(U)A:-$B}"), ["A", "B"]);
+    }
+
+    #[test]
+    fn test_referenced_vars() {
+        assert_eq!(
+            referenced_vars("${#A}${B:=${C}}${D/$E/$F}${G:$H:$I}`echo $J`"),
+            ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J"]
+        );
+        assert!(referenced_vars("$$HOME ~ ${#} $(( x ))").is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// 命令替换（`$(...)` 与反引号）的执行器 trait。
+/// Trait for abstracting command substitution.
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::command::MockRunner;
+/// use rush_var::{ExpandOptions, try_expand_with};
+/// let env = [("VERSION", "17")];
+/// let runner = MockRunner::default().with("java_home -v 17", "/opt/jdk-17");
+/// let options = ExpandOptions { runner: Some(&runner), ..Default::default() };
+/// assert_eq!(try_expand_with("$(java_home -v ${VERSION})/bin", &env, &options).unwrap(), "/opt/jdk-17/bin");
+/// ```
+pub trait CommandRunner {
+    /// 执行 `command` 并返回替换结果；返回 `Ok(None)` 时原样保留 `$(...)`，交给 shell 在启动时执行。
+    fn run(&self, command: &str) -> Result<Option<String>, String>;
+}
+
+/// 为任意已实现 CommandRunner 的类型的引用自动实现 CommandRunner
+impl<T: CommandRunner + ?Sized> CommandRunner for &T {
+    fn run(&self, command: &str) -> Result<Option<String>, String> {
+        (**self).run(command)
+    }
+}
+
+/// 不执行任何命令，`$(...)` 与反引号原样保留，未指定执行器时的默认行为
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Verbatim;
+
+impl CommandRunner for Verbatim {
+    fn run(&self, _command: &str) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+}
+
+/// 通过 `sh -c` 执行命令，退出状态非零或超时（终止进程）时报错；与 shell 一致，去掉输出末尾的换行
+#[derive(Debug, Clone)]
+pub struct ShellRunner {
+    pub shell: String,
+    pub timeout: Duration,
+}
+
+impl Default for ShellRunner {
+    fn default() -> Self {
+        Self {
+            shell: "sh".to_string(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ShellRunner {
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            ..Default::default()
+        }
+    }
+}
+
+impl CommandRunner for ShellRunner {
+    fn run(&self, command: &str) -> Result<Option<String>, String> {
+        let mut child = Command::new(&self.shell)
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("failed to spawn {}: {e}", self.shell))?;
+        // 在后台读取输出，避免输出较多时子进程阻塞在管道上
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let reader = std::thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).map(|_| output)
+        });
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            match child.try_wait().map_err(|e| e.to_string())? {
+                Some(status) => break status,
+                None if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!("timed out after {:?}", self.timeout));
+                }
+                None => std::thread::sleep(Duration::from_millis(5)),
+            }
+        };
+        let output = reader
+            .join()
+            .map_err(|_| "failed to read output".to_string())?
+            .map_err(|e| e.to_string())?;
+        // 失败命令的部分输出不可信，不写入结果
+        if !status.success() {
+            return Err(status.to_string());
+        }
+        let output = String::from_utf8_lossy(&output);
+        Ok(Some(output.trim_end_matches('\n').to_string()))
+    }
+}
+
+/// 按命令文本返回预设输出，用于测试；未预设的命令返回错误
+#[derive(Debug, Clone, Default)]
+pub struct MockRunner {
+    outputs: HashMap<String, String>,
+}
+
+impl MockRunner {
+    pub fn with(mut self, command: impl Into<String>, output: impl Into<String>) -> Self {
+        self.outputs.insert(command.into(), output.into());
+        self
+    }
+}
+
+impl CommandRunner for MockRunner {
+    fn run(&self, command: &str) -> Result<Option<String>, String> {
+        match self.outputs.get(command) {
+            Some(output) => Ok(Some(output.clone())),
+            None => Err(format!("unexpected command: {command}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_runner() {
+        let runner = ShellRunner::default();
+        assert_eq!(runner.run("printf 'a\\n\\n'").unwrap(), Some("a".to_string()));
+        assert_eq!(runner.run("printf 'a'; exit 3"), Err("exit status: 3".to_string()));
+        let runner = ShellRunner::with_timeout(Duration::from_millis(50));
+        assert!(runner.run("sleep 5").unwrap_err().starts_with("timed out"));
+    }
+}
@@ -0,0 +1,302 @@
+use crate::env_source::{EnvSource, EnvSourceChain};
+use crate::error::DotenvError;
+use std::borrow::Cow;
+use std::path::Path;
+
+/// 解析后的 dotenv 文件，按出现顺序保存变量，重复定义的变量取最后一次的值。
+///
+/// 支持的语法：
+/// - `KEY=value`，可带 `export ` 前缀，`#` 开头的行与值后以空白分隔的 `# ...` 为注释
+/// - 单引号值原样保留，可跨行
+/// - 双引号值支持 `\n`、`\t`、`\r`、`\"`、`\\`、`\$` 转义，可跨行
+/// - 未加引号与双引号的值会展开 `$VAR` / `${VAR:-default}`，引用前面已定义的条目，找不到时回退到外部环境
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::dotenv::Dotenv;
+/// use rush_var::expand_env;
+/// let input = r#"
+/// ## 数据库配置
+/// export DB_HOST=localhost
+/// DB_URL="postgres://${DB_HOST}:${DB_PORT:-5432}/app"
+/// PASSWORD='pa$$word'
+/// "#;
+/// let dotenv = Dotenv::parse(input).unwrap();
+/// assert_eq!(dotenv.get("DB_URL"), Some("postgres://localhost:5432/app"));
+/// assert_eq!(dotenv.get("PASSWORD"), Some("pa$$word"));
+/// assert_eq!(expand_env("$DB_HOST", &dotenv), "localhost");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dotenv {
+    vars: Vec<(String, String)>,
+}
+
+impl Dotenv {
+    /// 解析 dotenv 文本，值中只能引用文件内前面定义的变量
+    pub fn parse(input: &str) -> Result<Self, DotenvError> {
+        Self::parse_with(input, &[("", ""); 0])
+    }
+
+    /// 同 [`Dotenv::parse`]，文件内找不到的变量从 `env` 中查找
+    pub fn parse_with(input: &str, env: &impl EnvSource) -> Result<Self, DotenvError> {
+        let mut dotenv = Dotenv::default();
+        let mut parser = Parser { input, pos: 0, line: 1 };
+        while let Some(entry) = parser.entry()? {
+            let chain = EnvSourceChain {
+                primary: &dotenv,
+                fallback: env,
+            };
+            let value = match entry.value {
+                Value::Literal(value) => value,
+                Value::Template(template) => crate::try_expand(&template, &chain).map_err(|source| DotenvError::Expand {
+                    line: entry.line,
+                    name: entry.name.clone(),
+                    source,
+                })?,
+            };
+            dotenv.insert(entry.name, value);
+        }
+        Ok(dotenv)
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, DotenvError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_path_with(path: impl AsRef<Path>, env: &impl EnvSource) -> Result<Self, DotenvError> {
+        Self::parse_with(&std::fs::read_to_string(path)?, env)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// 按首次出现的顺序遍历变量
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.vars.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.vars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    fn insert(&mut self, name: String, value: String) {
+        match self.vars.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, old)) => *old = value,
+            None => self.vars.push((name, value)),
+        }
+    }
+}
+
+impl EnvSource for Dotenv {
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        Dotenv::get(self, key).map(Cow::Borrowed)
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        self.vars.clone()
+    }
+}
+
+struct Entry {
+    line: usize,
+    name: String,
+    value: Value,
+}
+
+enum Value {
+    /// 单引号值，不做展开
+    Literal(String),
+    /// 交给 [`crate::try_expand`] 的文本，转义得到的字面 `$` 已转为 `$$`
+    Template(String),
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, line: usize, message: impl Into<String>) -> DotenvError {
+        DotenvError::Syntax {
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    /// 跳到下一行开头
+    fn skip_line(&mut self) {
+        while let Some(c) = self.bump() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    fn word(&mut self) -> &'a str {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+            self.bump();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn entry(&mut self) -> Result<Option<Entry>, DotenvError> {
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                None => return Ok(None),
+                Some('\n' | '\r') => {
+                    self.bump();
+                }
+                Some('#') => self.skip_line(),
+                Some(_) => break,
+            }
+        }
+        let line = self.line;
+        let mut name = self.word();
+        if name == "export" && matches!(self.peek(), Some(' ' | '\t')) {
+            self.skip_blank();
+            name = self.word();
+        }
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error(line, "expected variable name"));
+        }
+        self.skip_blank();
+        if self.bump() != Some('=') {
+            return Err(self.error(line, format!("expected '=' after '{name}'")));
+        }
+        self.skip_blank();
+        let value = match self.peek() {
+            Some('\'') => Value::Literal(self.single_quoted(line)?),
+            Some('"') => Value::Template(self.double_quoted(line)?),
+            _ => Value::Template(self.unquoted()),
+        };
+        self.skip_blank();
+        match self.peek() {
+            None => {}
+            Some('\n' | '\r' | '#') => self.skip_line(),
+            Some(c) => return Err(self.error(self.line, format!("unexpected '{c}' after value of '{name}'"))),
+        }
+        Ok(Some(Entry {
+            line,
+            name: name.to_string(),
+            value,
+        }))
+    }
+
+    fn single_quoted(&mut self, line: usize) -> Result<String, DotenvError> {
+        self.bump();
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error(line, "unterminated single quote")),
+                Some('\'') => return Ok(value),
+                Some(c) => value.push(c),
+            }
+        }
+    }
+
+    fn double_quoted(&mut self, line: usize) -> Result<String, DotenvError> {
+        self.bump();
+        let mut template = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error(line, "unterminated double quote")),
+                Some('"') => return Ok(template),
+                Some('\\') => match self.bump() {
+                    None => return Err(self.error(line, "unterminated double quote")),
+                    Some('n') => template.push('\n'),
+                    Some('t') => template.push('\t'),
+                    Some('r') => template.push('\r'),
+                    Some('$') => template.push_str("$$"),
+                    Some(c @ ('"' | '\\')) => template.push(c),
+                    Some(c) => {
+                        template.push('\\');
+                        template.push(c);
+                    }
+                },
+                Some(c) => template.push(c),
+            }
+        }
+    }
+
+    /// 读到行尾，去掉以空白分隔的行内注释与末尾空白
+    fn unquoted(&mut self) -> String {
+        let rest = &self.input[self.pos..];
+        let end = rest.find('\n').unwrap_or(rest.len());
+        let mut value = &rest[..end];
+        if let Some(comment) = value.find(" #").or_else(|| value.find("\t#")) {
+            value = &value[..comment];
+        }
+        let value = value.trim_end();
+        self.pos += value.len();
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_syntax() {
+        let input = "\
+# comment
+A=plain value # trailing comment
+export B = 'single $A `date` # not a comment'
+C=\"double\\t$A\\n\\$A \\\"quoted\\\"\"
+MULTI=\"line 1
+line 2\"
+EMPTY=
+A=override\r
+URL=${HOST:-localhost}:${PORT}/${A}
+";
+        let dotenv = Dotenv::parse_with(input, &[("PORT", "8080")]).unwrap();
+        assert_eq!(
+            vec![
+                ("A", "override"),
+                ("B", "single $A `date` # not a comment"),
+                ("C", "double\tplain value\n$A \"quoted\""),
+                ("MULTI", "line 1\nline 2"),
+                ("EMPTY", ""),
+                ("URL", "localhost:8080/override"),
+            ],
+            dotenv.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input: &str| Dotenv::parse(input).unwrap_err().to_string();
+        assert_eq!("line 2: expected '=' after 'B'", error("A=1\nB\n"));
+        assert_eq!("line 1: expected variable name", error("1A=1"));
+        assert_eq!("line 2: unterminated double quote", error("\nA=\"abc\n\n"));
+        assert_eq!("line 1: unexpected 'x' after value of 'A'", error("A='a'x"));
+        assert!(error("A=1\nB=${A").starts_with("line 2: cannot expand B: unterminated '${'"));
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap};
 
 /// 环境变量查找源 trait。用于支持多种来源的变量查找。
@@ -12,48 +13,91 @@ use std::collections::{BTreeMap, HashMap};
 /// ```
 pub trait EnvSource {
     /// 获取指定 key 的变量值，如果不存在则返回 None。
-    fn get(&self, key: &str) -> Option<String>;
+    /// 能直接借出值的源返回 `Cow::Borrowed`，避免每次查找都分配内存。
+    fn get(&self, key: &str) -> Option<Cow<'_, str>>;
+
+    /// 枚举全部变量，用于 [`crate::env_stack::EnvStack::vars`]；无法枚举的源（如闭包）返回空。
+    fn vars(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
 }
 
 /// 为任意已实现 EnvSource 的类型的引用自动实现 EnvSource
 impl<T: EnvSource + ?Sized> EnvSource for &T {
-    fn get(&self, key: &str) -> Option<String> {
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
         (**self).get(key)
     }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        (**self).vars()
+    }
 }
 
 /// HashMap 作为环境变量源
 impl EnvSource for HashMap<String, String> {
-    fn get(&self, key: &str) -> Option<String> {
-        self.get(key).cloned()
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.get(key).map(|value| Cow::Borrowed(value.as_str()))
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        self.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
     }
 }
 
 /// BTreeMap 作为环境变量源
 impl EnvSource for BTreeMap<String, String> {
-    fn get(&self, key: &str) -> Option<String> {
-        self.get(key).cloned()
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.get(key).map(|value| Cow::Borrowed(value.as_str()))
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        self.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
     }
 }
 
 /// 切片 &[(&str, &str)] 作为环境变量源，适用于快速mock和常量环境。
 impl<'a> EnvSource for &'a [(&'a str, &'a str)] {
-    fn get(&self, key: &str) -> Option<String> {
-        self.iter().find(|(k, _)| *k == key).map(|(_, v)| (*v).to_owned())
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.iter().find(|(k, _)| *k == key).map(|(_, v)| Cow::Borrowed(*v))
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        self.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+}
+
+/// 定长数组 [(&str, &str); N] 作为环境变量源，便于直接 `&[("FOO", "bar")]` 传参。
+impl<const N: usize> EnvSource for [(&str, &str); N] {
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.iter().find(|(k, _)| *k == key).map(|(_, v)| Cow::Borrowed(*v))
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        self.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
     }
 }
 
 /// 系统环境变量（字符串）
 impl EnvSource for std::env::Vars {
-    fn get(&self, key: &str) -> Option<String> {
-        std::env::var(key).ok()
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        std::env::var(key).ok().map(Cow::Owned)
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        std::env::vars().collect()
     }
 }
 
 /// 系统环境变量（OsString）
 impl EnvSource for std::env::VarsOs {
-    fn get(&self, key: &str) -> Option<String> {
-        std::env::var_os(key).and_then(|v| v.into_string().ok())
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        std::env::var_os(key).and_then(|v| v.into_string().ok()).map(Cow::Owned)
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        std::env::vars_os()
+            .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+            .collect()
     }
 }
 
@@ -72,13 +116,13 @@ impl<F> EnvSource for FnEnvSource<F>
 where
     for<'a> F: Fn(&'a str) -> Option<String>,
 {
-    fn get(&self, key: &str) -> Option<String> {
-        self.0(key)
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.0(key).map(Cow::Owned)
     }
 }
 
 /// 链式环境变量源：优先查询 primary，没有再查 fallback。
-/// 常用于“临时变量+系统变量”的多层环境方案。
+/// 常用于“临时变量+系统变量”的两层环境方案，更多层或需要修改变量时使用 [`crate::env_stack::EnvStack`]。
 pub struct EnvSourceChain<A, B> {
     /// 主查找源（优先）
     pub primary: A,
@@ -87,7 +131,13 @@ pub struct EnvSourceChain<A, B> {
 }
 
 impl<A: EnvSource, B: EnvSource> EnvSource for EnvSourceChain<A, B> {
-    fn get(&self, key: &str) -> Option<String> {
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
         self.primary.get(key).or_else(|| self.fallback.get(key))
     }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        let mut vars = self.primary.vars();
+        vars.extend(self.fallback.vars().into_iter().filter(|(k, _)| self.primary.get(k).is_none()));
+        vars
+    }
 }
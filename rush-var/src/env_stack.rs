@@ -0,0 +1,139 @@
+use crate::env_source::EnvSource;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+/// 分层的可写环境，用于在内存中模拟 shell 环境。
+///
+/// 底部是任意数量的只读源（后加入的优先），其上是作用域栈：`set`/`unset` 只修改最顶层作用域，
+/// `pop_scope` 丢弃该作用域中的全部修改。`unset` 会遮蔽下层同名变量，而不是删除它们。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::env_stack::EnvStack;
+/// use rush_var::expand_env;
+/// let system = [("HOME", "/home/me"), ("EDITOR", "vi")];
+/// let mut env = EnvStack::new().with_source(system);
+/// env.set("EDITOR", "nvim");
+/// env.push_scope();
+/// env.set("BIN", "$HOME/bin");
+/// env.unset("HOME");
+/// assert_eq!(expand_env("$EDITOR:$HOME:$BIN", &env), "nvim::$HOME/bin");
+/// env.pop_scope();
+/// assert_eq!(expand_env("$EDITOR:$HOME:$BIN", &env), "nvim:/home/me:");
+/// ```
+pub struct EnvStack<'a> {
+    sources: Vec<Box<dyn EnvSource + 'a>>,
+    /// `None` 表示在该作用域中被 unset
+    scopes: Vec<HashMap<String, Option<String>>>,
+}
+
+impl Default for EnvStack<'_> {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            scopes: vec![HashMap::new()],
+        }
+    }
+}
+
+impl<'a> EnvStack<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加入一个只读源，优先级高于已有的源，低于所有作用域
+    pub fn with_source(mut self, source: impl EnvSource + 'a) -> Self {
+        self.push_source(source);
+        self
+    }
+
+    pub fn push_source(&mut self, source: impl EnvSource + 'a) {
+        self.sources.push(Box::new(source));
+    }
+
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.top().insert(key.into(), Some(value.into()));
+    }
+
+    pub fn unset(&mut self, key: impl Into<String>) {
+        self.top().insert(key.into(), None);
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// 丢弃最顶层作用域；最底层的作用域不会被移除，只会被清空
+    pub fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        } else {
+            self.scopes[0].clear();
+        }
+    }
+
+    /// 作用域的层数，至少为 1
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    fn top(&mut self) -> &mut HashMap<String, Option<String>> {
+        self.scopes.last_mut().expect("EnvStack 至少有一层作用域")
+    }
+
+    /// 当前生效的全部变量，按名称排序
+    pub fn vars(&self) -> BTreeMap<String, String> {
+        let mut vars: BTreeMap<String, String> = self.sources.iter().flat_map(|source| source.vars()).collect();
+        for scope in &self.scopes {
+            for (key, value) in scope {
+                match value {
+                    Some(value) => vars.insert(key.clone(), value.clone()),
+                    None => vars.remove(key),
+                };
+            }
+        }
+        vars
+    }
+}
+
+impl EnvSource for EnvStack<'_> {
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        if let Some(value) = self.scopes.iter().rev().find_map(|scope| scope.get(key)) {
+            return value.as_deref().map(Cow::Borrowed);
+        }
+        self.sources.iter().rev().find_map(|source| source.get(key))
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        EnvStack::vars(self).into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_and_scopes() {
+        let base = [("A", "base"), ("B", "base"), ("C", "base")];
+        let mut overrides = HashMap::new();
+        overrides.insert("B".to_string(), "override".to_string());
+        let mut env = EnvStack::new().with_source(base).with_source(overrides);
+        env.set("C", "set");
+        env.push_scope();
+        env.unset("A");
+        env.set("D", "scoped");
+        assert_eq!(2, env.depth());
+        assert_eq!(None, env.get("A"));
+        assert_eq!(
+            vec![("B", "override"), ("C", "set"), ("D", "scoped")],
+            env.vars().iter().map(|(k, v)| (k.as_str(), v.as_str())).collect::<Vec<_>>()
+        );
+        env.pop_scope();
+        assert_eq!(Some("base"), env.get("A").as_deref());
+        assert_eq!(None, env.get("D"));
+        env.pop_scope();
+        assert_eq!(1, env.depth());
+        assert_eq!(Some("base"), env.get("C").as_deref());
+    }
+}
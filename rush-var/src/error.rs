@@ -0,0 +1,151 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// 展开失败的原因，`span` 为出错片段在输入中的字节区间。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::error::ExpandError;
+/// use rush_var::try_expand;
+/// let env = [("FOO", "bar")];
+/// let err = try_expand("path=${FOO", &env).unwrap_err();
+/// assert_eq!(err, ExpandError::UnterminatedBrace { span: 5..10 });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpandError {
+    /// `${` 缺少配对的 `}`
+    UnterminatedBrace { span: Range<usize> },
+    /// `$(` 缺少配对的 `)`，或反引号未闭合
+    UnterminatedSubstitution { span: Range<usize> },
+    /// `${...}` 中无法识别的内容，例如 `${FOO BAR}`
+    BadSubstitution { span: Range<usize> },
+    /// 严格模式下引用了未定义的变量
+    UndefinedVariable { name: String, span: Range<usize> },
+    /// `${VAR:?message}` / `${VAR?message}` 触发的错误
+    ParameterError { name: String, message: String, span: Range<usize> },
+    /// 命令替换执行失败
+    CommandFailed {
+        command: String,
+        message: String,
+        span: Range<usize>,
+    },
+    /// 递归展开时变量引用成环，`path` 形如 `["A", "B", "A"]`
+    RecursionCycle { path: Vec<String>, span: Range<usize> },
+    /// 递归展开超过最大深度
+    RecursionLimit { limit: usize, span: Range<usize> },
+}
+
+impl ExpandError {
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            ExpandError::UnterminatedBrace { span }
+            | ExpandError::UnterminatedSubstitution { span }
+            | ExpandError::BadSubstitution { span }
+            | ExpandError::UndefinedVariable { span, .. }
+            | ExpandError::ParameterError { span, .. }
+            | ExpandError::CommandFailed { span, .. }
+            | ExpandError::RecursionCycle { span, .. }
+            | ExpandError::RecursionLimit { span, .. } => span.clone(),
+        }
+    }
+
+    /// 替换出错的字节区间，例如把子串中的位置换算为整段输入中的位置
+    pub fn with_span(mut self, new_span: Range<usize>) -> Self {
+        match &mut self {
+            ExpandError::UnterminatedBrace { span }
+            | ExpandError::UnterminatedSubstitution { span }
+            | ExpandError::BadSubstitution { span }
+            | ExpandError::UndefinedVariable { span, .. }
+            | ExpandError::ParameterError { span, .. }
+            | ExpandError::CommandFailed { span, .. }
+            | ExpandError::RecursionCycle { span, .. }
+            | ExpandError::RecursionLimit { span, .. } => *span = new_span,
+        }
+        self
+    }
+
+    /// 输出原文并在出错片段下方标注 `^`，便于在配置中定位
+    ///
+    /// ```rust
+    /// use rush_var::try_expand;
+    /// let env = [("FOO", "bar")];
+    /// let input = "a ${FOO";
+    /// let err = try_expand(input, &env).unwrap_err();
+    /// assert_eq!(err.annotate(input), "a ${FOO\n  ^^^^^");
+    /// ```
+    pub fn annotate(&self, input: &str) -> String {
+        let span = self.span();
+        let start = input.get(..span.start).map_or(0, |prefix| prefix.chars().count());
+        let width = input.get(span).map_or(1, |text| text.chars().count().max(1));
+        format!("{input}\n{}{}", " ".repeat(start), "^".repeat(width))
+    }
+}
+
+impl Display for ExpandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpandError::UnterminatedBrace { span } => write!(f, "unterminated '${{' at {span:?}"),
+            ExpandError::UnterminatedSubstitution { span } => write!(f, "unterminated command substitution at {span:?}"),
+            ExpandError::BadSubstitution { span } => write!(f, "bad substitution at {span:?}"),
+            ExpandError::UndefinedVariable { name, span } => write!(f, "undefined variable '{name}' at {span:?}"),
+            ExpandError::ParameterError { name, message, span } => {
+                if message.is_empty() {
+                    write!(f, "{name}: parameter null or not set at {span:?}")
+                } else {
+                    write!(f, "{name}: {message} at {span:?}")
+                }
+            }
+            ExpandError::CommandFailed { command, message, span } => {
+                write!(f, "command '{command}' failed: {message} at {span:?}")
+            }
+            ExpandError::RecursionCycle { path, span } => write!(f, "variable cycle {} at {span:?}", path.join(" -> ")),
+            ExpandError::RecursionLimit { limit, span } => write!(f, "recursion limit {limit} exceeded at {span:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ExpandError {}
+
+/// 解析 dotenv 文件失败的原因，`line` 从 1 开始计数。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::dotenv::Dotenv;
+/// let err = Dotenv::parse("A=1\nB='unterminated").unwrap_err();
+/// assert_eq!(err.to_string(), "line 2: unterminated single quote");
+/// ```
+#[derive(Debug)]
+pub enum DotenvError {
+    /// 读取文件失败
+    Io(std::io::Error),
+    /// 无法识别的语法，例如缺少 `=` 或引号未闭合
+    Syntax { line: usize, message: String },
+    /// 值中的 `${...}` 展开失败，`span` 相对于该条目的值
+    Expand { line: usize, name: String, source: ExpandError },
+}
+
+impl Display for DotenvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DotenvError::Io(e) => write!(f, "{e}"),
+            DotenvError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            DotenvError::Expand { line, name, source } => write!(f, "line {line}: cannot expand {name}: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for DotenvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DotenvError::Io(e) => Some(e),
+            DotenvError::Syntax { .. } => None,
+            DotenvError::Expand { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<std::io::Error> for DotenvError {
+    fn from(e: std::io::Error) -> Self {
+        DotenvError::Io(e)
+    }
+}
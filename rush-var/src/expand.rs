@@ -0,0 +1,683 @@
+//! 在 [`crate::ast`] 语法树上对 `$VAR` / `${...}` 参数展开求值。
+//!
+//! `${...}` 中支持的操作与 Bash 保持一致：
+//!
+//! | 语法                          | 含义                                       |
+//! |-------------------------------|--------------------------------------------|
+//! | `${VAR:-w}` / `${VAR-w}`      | 未定义或为空（无冒号时仅未定义）时取 `w`   |
+//! | `${VAR:=w}` / `${VAR=w}`      | 同上，并在本次展开中把 `w` 赋给 `VAR`      |
+//! | `${VAR:?w}` / `${VAR?w}`      | 未定义或为空时以 `w` 为信息报错            |
+//! | `${VAR:+w}` / `${VAR+w}`      | 已定义且非空（无冒号时仅已定义）时取 `w`   |
+//! | `${#VAR}`                     | 值的字符数                                 |
+//! | `${VAR#p}` / `${VAR##p}`      | 去掉最短/最长匹配前缀                      |
+//! | `${VAR%p}` / `${VAR%%p}`      | 去掉最短/最长匹配后缀                      |
+//! | `${VAR/p/r}` / `${VAR//p/r}`  | 替换第一个/全部匹配，`/#`、`/%` 锚定首尾   |
+//! | `${VAR:offset}` / `${VAR:offset:len}` | 按字符截取子串，负数从末尾计算     |
+//!
+//! [`ExpandOptions::dialect`] 为 [`crate::ast::Dialect::Zsh`] 时另外支持 zsh 的修饰符与参数标志：
+//!
+//! | 语法                          | 含义                                       |
+//! |-------------------------------|--------------------------------------------|
+//! | `${VAR:h}` / `${VAR:t}`       | 去掉最后一级路径 / 只保留最后一级路径      |
+//! | `${VAR:r}` / `${VAR:e}`       | 去掉扩展名 / 只保留扩展名                  |
+//! | `${VAR:a}` / `${VAR:A}`       | 绝对路径 / 绝对路径并解析符号链接          |
+//! | `${VAR:u}` / `${VAR:l}`       | 转为大写 / 小写，修饰符可以串联如 `:t:r`   |
+//! | `${(U)VAR}` / `${(L)VAR}` / `${(C)VAR}` | 大写 / 小写 / 单词首字母大写，可与其他操作组合 |
+//!
+//! `$(...)` 与反引号默认原样保留；设置了 [`ExpandOptions::runner`] 时先展开命令中的变量，
+//! 再交给执行器求值。`$((...))` 算术展开始终原样保留。
+//!
+//! 设置了 [`ExpandOptions::home`] 时，位于词首或 `:` 之后的 `~`、`~user`、`~+`、`~-`
+//! 依次展开为 `HOME`、用户主目录、`PWD` 与 `OLDPWD`，找不到时保留原文。
+//!
+//! [`ExpandOptions::syntax`] 可以另外（或改为）识别 `%VAR%`、`$env:VAR` 与 `{{ VAR }}`，
+//! 它们只是普通的变量引用，不支持操作符。
+
+use crate::ExpandOptions;
+use crate::ast::{Anchor, Flag, Modifier, Node, Operator, Syntax, parse_with};
+use crate::env_source::EnvSource;
+use crate::error::ExpandError;
+use crate::pattern::Pattern;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::Range;
+
+/// 在 [`Node`] 序列上求值，`${VAR:=w}` 的赋值只在本次展开内可见。
+///
+/// `fallible` 为 false 时（即 [`crate::expand_env`]）所有错误都按宽松规则降级：
+/// 未闭合的 `${` 视为在末尾闭合，`${VAR:?msg}` 与无法识别的 `${...}` 展开为空串，
+/// 循环引用或超过深度的变量保留原值不再展开。
+pub(crate) struct Expander<'e, E: EnvSource + ?Sized> {
+    env: &'e E,
+    options: &'e ExpandOptions<'e>,
+    fallible: bool,
+    recursive: bool,
+    assigned: HashMap<String, String>,
+    /// 正在解析的变量，用于检测循环引用
+    resolving: Vec<String>,
+    /// 最外层引用解析出的值，同一变量多次引用时不必重复展开
+    resolved: HashMap<String, String>,
+}
+
+impl<'e, E: EnvSource + ?Sized> Expander<'e, E> {
+    pub(crate) fn new(env: &'e E, options: &'e ExpandOptions<'e>, fallible: bool) -> Self {
+        Self {
+            env,
+            options,
+            fallible,
+            recursive: false,
+            assigned: HashMap::new(),
+            resolving: Vec::new(),
+            resolved: HashMap::new(),
+        }
+    }
+
+    /// 变量的值在代入前先单独展开，值中的 `$$` 只展开一次
+    pub(crate) fn recursive(mut self) -> Self {
+        self.recursive = true;
+        self
+    }
+
+    /// 宽松模式下返回 `fallback`，否则返回错误
+    fn fail(&self, error: ExpandError, fallback: String) -> Result<String, ExpandError> {
+        if self.fallible { Err(error) } else { Ok(fallback) }
+    }
+
+    /// 查找变量，递归模式下返回展开后的值，`span` 为引用处的区间
+    fn lookup(&mut self, name: &str, span: &Range<usize>) -> Result<Option<Cow<'e, str>>, ExpandError> {
+        if let Some(value) = self.assigned.get(name) {
+            // 赋值时已经展开过
+            return Ok(Some(Cow::Owned(value.clone())));
+        }
+        match self.env.get(name) {
+            Some(value) if self.recursive && self.has_references(&value) => {
+                // 嵌套解析的结果依赖当前的解析路径（循环检测），只缓存最外层的结果
+                if !self.resolving.is_empty() {
+                    return self.resolve(name, &value, span).map(|value| Some(Cow::Owned(value)));
+                }
+                if let Some(resolved) = self.resolved.get(name) {
+                    return Ok(Some(Cow::Owned(resolved.clone())));
+                }
+                let resolved = self.resolve(name, &value, span)?;
+                self.resolved.insert(name.to_string(), resolved.clone());
+                Ok(Some(Cow::Owned(resolved)))
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn resolve(&mut self, name: &str, value: &str, span: &Range<usize>) -> Result<String, ExpandError> {
+        if let Some(start) = self.resolving.iter().position(|resolving| resolving == name) {
+            let mut path = self.resolving[start..].to_vec();
+            path.push(name.to_string());
+            return self.fail(ExpandError::RecursionCycle { path, span: span.clone() }, value.to_string());
+        }
+        if self.resolving.len() >= self.options.max_depth {
+            let limit = self.options.max_depth;
+            return self.fail(ExpandError::RecursionLimit { limit, span: span.clone() }, value.to_string());
+        }
+        self.resolving.push(name.to_string());
+        // 值中的错误无法对应回原文，统一标注在引用处
+        let resolved = parse_with(value, 0, self.fallible, self.options.dialect, self.options.syntax)
+            .and_then(|nodes| self.eval(&nodes))
+            .map_err(|error| error.with_span(span.clone()));
+        self.resolving.pop();
+        resolved
+    }
+
+    /// 查找变量，严格模式下未定义即报错
+    fn lookup_defined(&mut self, name: &str, span: &Range<usize>) -> Result<Cow<'e, str>, ExpandError> {
+        match self.lookup(name, span)? {
+            Some(value) => Ok(value),
+            None if self.options.strict && self.fallible => Err(ExpandError::UndefinedVariable {
+                name: name.to_string(),
+                span: span.clone(),
+            }),
+            None => Ok(Cow::Borrowed("")),
+        }
+    }
+
+    /// 输入中没有需要展开的内容
+    pub(crate) fn is_plain(&self, input: &str) -> bool {
+        let tilde = self.options.home.is_some();
+        !(self.has_references(input) || tilde && input.contains('~'))
+    }
+
+    /// 文本中可能含有按 [`ExpandOptions::syntax`] 识别的变量引用或命令替换
+    fn has_references(&self, text: &str) -> bool {
+        let syntax = self.options.syntax;
+        text.bytes().any(|b| match b {
+            b'$' | b'`' => true,
+            b'%' => syntax.contains(Syntax::BATCH),
+            b'{' => syntax.contains(Syntax::MUSTACHE),
+            _ => false,
+        })
+    }
+
+    pub(crate) fn expand(&mut self, input: &str) -> Result<String, ExpandError> {
+        if self.is_plain(input) {
+            return Ok(input.to_string());
+        }
+        let nodes = parse_with(input, 0, self.fallible, self.options.dialect, self.options.syntax)?;
+        let mut result = String::with_capacity(input.len());
+        self.eval_into(&nodes, &mut result)?;
+        Ok(result)
+    }
+
+    fn eval(&mut self, nodes: &[Node]) -> Result<String, ExpandError> {
+        let mut result = String::new();
+        self.eval_into(nodes, &mut result)?;
+        Ok(result)
+    }
+
+    /// 求值并追加到 `result`，避免为每个节点分配中间字符串
+    pub(crate) fn eval_into(&mut self, nodes: &[Node], result: &mut String) -> Result<(), ExpandError> {
+        for node in nodes {
+            match node {
+                Node::Literal(text) => result.push_str(text),
+                Node::Var { name, op: None, span } => result.push_str(&self.lookup_defined(name, span)?),
+                Node::Var { name, op: Some(op), span } => result.push_str(&self.eval_op(name, op, span)?),
+                Node::Length { name, span } => {
+                    let value = self.lookup_defined(name, span)?;
+                    write!(result, "{}", value.chars().count()).expect("写入 String 不会失败");
+                }
+                Node::Tilde { prefix, .. } => match self.tilde(prefix) {
+                    Some(dir) => result.push_str(&dir),
+                    None => {
+                        result.push('~');
+                        result.push_str(prefix);
+                    }
+                },
+                Node::Command { command, backtick, span } => {
+                    result.push_str(&self.substitute(command, *backtick, span)?);
+                }
+                Node::Flagged { flags, node } => {
+                    let value = self.eval(std::slice::from_ref(node))?;
+                    result.push_str(&flags.iter().fold(value, |value, flag| apply_flag(&value, *flag)));
+                }
+                Node::Invalid { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 命令替换：未指定执行器或执行器选择保留时返回原文，否则先展开命令中的变量再执行
+    fn substitute(&mut self, command: &str, backtick: bool, span: &Range<usize>) -> Result<String, ExpandError> {
+        let original = if backtick {
+            format!("`{command}`")
+        } else {
+            format!("$({command})")
+        };
+        let Some(runner) = self.options.runner else {
+            return Ok(original);
+        };
+        let base = span.start + if backtick { 1 } else { 2 };
+        let command = self.eval(&parse_with(
+            command,
+            base,
+            self.fallible,
+            self.options.dialect,
+            self.options.syntax,
+        )?)?;
+        match runner.run(&command) {
+            Ok(Some(output)) => Ok(output),
+            Ok(None) => Ok(original),
+            Err(message) => {
+                let span = span.clone();
+                self.fail(ExpandError::CommandFailed { command, message, span }, original)
+            }
+        }
+    }
+
+    /// `~` 之后、`/` 之前的部分对应的目录；未开启波浪号展开、位于变量值中或查找失败时返回 None
+    fn tilde(&self, prefix: &str) -> Option<String> {
+        let home = self.options.home.filter(|_| self.resolving.is_empty())?;
+        match prefix {
+            "" => self.env.get("HOME").map(Cow::into_owned),
+            "+" => self.env.get("PWD").map(Cow::into_owned),
+            "-" => self.env.get("OLDPWD").map(Cow::into_owned),
+            user => home.home_dir(user),
+        }
+    }
+
+    /// 求值带操作符的 `${VAR op word}`，`span` 为整个 `${...}` 的区间
+    fn eval_op(&mut self, name: &str, op: &Operator, span: &Range<usize>) -> Result<Cow<'e, str>, ExpandError> {
+        match op {
+            Operator::Default { colon, word } => match self.lookup(name, span)? {
+                Some(value) if !(*colon && value.is_empty()) => Ok(value),
+                _ => self.eval(word).map(Cow::Owned),
+            },
+            Operator::Assign { colon, word } => match self.lookup(name, span)? {
+                Some(value) if !(*colon && value.is_empty()) => Ok(value),
+                _ => {
+                    let word = self.eval(word)?;
+                    self.assigned.insert(name.to_string(), word.clone());
+                    // 赋值可能改变其他变量的展开结果
+                    self.resolved.clear();
+                    Ok(Cow::Owned(word))
+                }
+            },
+            Operator::Error { colon, word } => match self.lookup(name, span)? {
+                Some(value) if !(*colon && value.is_empty()) => Ok(value),
+                _ => {
+                    let message = self.eval(word)?;
+                    let error = ExpandError::ParameterError {
+                        name: name.to_string(),
+                        message,
+                        span: span.clone(),
+                    };
+                    self.fail(error, String::new()).map(Cow::Owned)
+                }
+            },
+            Operator::Alternative { colon, word } => match self.lookup(name, span)? {
+                Some(value) if !(*colon && value.is_empty()) => self.eval(word).map(Cow::Owned),
+                _ => Ok(Cow::Borrowed("")),
+            },
+            Operator::RemovePrefix { longest, pattern } => {
+                let value = self.lookup_defined(name, span)?;
+                let pattern = Pattern::new(&self.eval(pattern)?);
+                Ok(Cow::Owned(remove_prefix(&value, &pattern, *longest)))
+            }
+            Operator::RemoveSuffix { longest, pattern } => {
+                let value = self.lookup_defined(name, span)?;
+                let pattern = Pattern::new(&self.eval(pattern)?);
+                Ok(Cow::Owned(remove_suffix(&value, &pattern, *longest)))
+            }
+            Operator::Replace {
+                all,
+                anchor,
+                pattern,
+                replacement,
+            } => {
+                let value = self.lookup_defined(name, span)?;
+                let pattern = Pattern::new(&self.eval(pattern)?);
+                let replacement = self.eval(replacement)?;
+                Ok(Cow::Owned(replace(&value, &pattern, &replacement, *all, *anchor)))
+            }
+            Operator::Substring { offset, length } => {
+                let value = self.lookup_defined(name, span)?;
+                let offset = parse_number(&self.eval(offset)?);
+                let length = match length {
+                    Some(length) => Some(parse_number(&self.eval(length)?)),
+                    None => None,
+                };
+                match (offset, length) {
+                    (Some(offset), None) => Ok(Cow::Owned(substring(&value, offset, None))),
+                    (Some(offset), Some(Some(length))) => Ok(Cow::Owned(substring(&value, offset, Some(length)))),
+                    _ => self
+                        .fail(ExpandError::BadSubstitution { span: span.clone() }, String::new())
+                        .map(Cow::Owned),
+                }
+            }
+            Operator::Modifiers(modifiers) => {
+                let value = self.lookup_defined(name, span)?;
+                let value = modifiers
+                    .iter()
+                    .fold(value.into_owned(), |value, modifier| apply_modifier(&value, *modifier));
+                Ok(Cow::Owned(value))
+            }
+        }
+    }
+}
+
+fn apply_modifier(value: &str, modifier: Modifier) -> String {
+    match modifier {
+        Modifier::Head => match value.rfind('/') {
+            Some(0) => "/".to_string(),
+            Some(slash) => value[..slash].to_string(),
+            None => ".".to_string(),
+        },
+        Modifier::Tail => value.rsplit('/').next().unwrap_or_default().to_string(),
+        Modifier::Root => match extension_dot(value) {
+            Some(dot) => value[..dot].to_string(),
+            None => value.to_string(),
+        },
+        Modifier::Extension => extension_dot(value).map_or_else(String::new, |dot| value[dot + 1..].to_string()),
+        Modifier::Absolute => absolute(value),
+        Modifier::Resolve => {
+            std::fs::canonicalize(absolute(value)).map_or_else(|_| absolute(value), |path| path.to_string_lossy().into_owned())
+        }
+        Modifier::Upper => value.to_uppercase(),
+        Modifier::Lower => value.to_lowercase(),
+    }
+}
+
+/// 最后一级路径中扩展名前的 `.`，隐藏文件开头的 `.` 不算
+fn extension_dot(value: &str) -> Option<usize> {
+    let start = value.rfind('/').map_or(0, |slash| slash + 1);
+    value[start..].rfind('.').filter(|&dot| dot > 0).map(|dot| start + dot)
+}
+
+/// 相对当前目录转为绝对路径，并按字面化简 `.`、`..` 与重复的 `/`
+fn absolute(value: &str) -> String {
+    let path = if value.starts_with('/') {
+        value.to_string()
+    } else {
+        let cwd = std::env::current_dir()
+            .map(|cwd| cwd.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("{cwd}/{value}")
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    format!("/{}", parts.join("/"))
+}
+
+fn apply_flag(value: &str, flag: Flag) -> String {
+    match flag {
+        Flag::Upper => value.to_uppercase(),
+        Flag::Lower => value.to_lowercase(),
+        Flag::Capitalize => {
+            let mut result = String::with_capacity(value.len());
+            let mut word_start = true;
+            for c in value.chars() {
+                if word_start {
+                    result.extend(c.to_uppercase());
+                } else {
+                    result.extend(c.to_lowercase());
+                }
+                word_start = !c.is_alphanumeric();
+            }
+            result
+        }
+    }
+}
+
+/// 解析子串的偏移量/长度，允许空白、正负号与一层括号，如 ` -3`、`(-3)`
+fn parse_number(expr: &str) -> Option<i64> {
+    let expr = expr.trim();
+    let expr = expr
+        .strip_prefix('(')
+        .and_then(|expr| expr.strip_suffix(')'))
+        .map_or(expr, str::trim);
+    if expr.is_empty() {
+        return Some(0);
+    }
+    expr.parse().ok()
+}
+
+fn remove_prefix(value: &str, pattern: &Pattern, longest: bool) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let mut ends: Box<dyn Iterator<Item = usize>> = if longest {
+        Box::new((0..=chars.len()).rev())
+    } else {
+        Box::new(0..=chars.len())
+    };
+    match ends.find(|&end| pattern.matches(&chars[..end])) {
+        Some(end) => chars[end..].iter().collect(),
+        None => value.to_string(),
+    }
+}
+
+fn remove_suffix(value: &str, pattern: &Pattern, longest: bool) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let mut starts: Box<dyn Iterator<Item = usize>> = if longest {
+        Box::new(0..=chars.len())
+    } else {
+        Box::new((0..=chars.len()).rev())
+    };
+    match starts.find(|&start| pattern.matches(&chars[start..])) {
+        Some(start) => chars[..start].iter().collect(),
+        None => value.to_string(),
+    }
+}
+
+fn replace(value: &str, pattern: &Pattern, replacement: &str, all: bool, anchor: Option<Anchor>) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    match anchor {
+        Some(Anchor::Start) => match (0..=chars.len()).rev().find(|&end| pattern.matches(&chars[..end])) {
+            Some(end) => format!("{replacement}{}", chars[end..].iter().collect::<String>()),
+            None => value.to_string(),
+        },
+        Some(Anchor::End) => match (0..=chars.len()).find(|&start| pattern.matches(&chars[start..])) {
+            Some(start) => format!("{}{replacement}", chars[..start].iter().collect::<String>()),
+            None => value.to_string(),
+        },
+        None => {
+            if pattern.is_empty() {
+                return value.to_string();
+            }
+            let mut result = String::new();
+            let mut start = 0;
+            let mut replaced = false;
+            while start < chars.len() {
+                if !replaced || all {
+                    // 从当前位置寻找最长的非空匹配
+                    if let Some(end) = (start + 1..=chars.len()).rev().find(|&end| pattern.matches(&chars[start..end])) {
+                        result.push_str(replacement);
+                        start = end;
+                        replaced = true;
+                        continue;
+                    }
+                }
+                result.push(chars[start]);
+                start += 1;
+            }
+            result
+        }
+    }
+}
+
+fn substring(value: &str, offset: i64, length: Option<i64>) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let len = chars.len() as i64;
+    let start = if offset < 0 { len + offset } else { offset };
+    if start < 0 || start > len {
+        return String::new();
+    }
+    let end = match length {
+        None => len,
+        Some(length) if length < 0 => len + length,
+        Some(length) => (start + length).min(len),
+    };
+    if end < start {
+        return String::new();
+    }
+    chars[start as usize..end as usize].iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Dialect, Syntax};
+    use crate::error::ExpandError;
+    use crate::{ExpandOptions, expand_env, try_expand_recursive_with, try_expand_with};
+
+    /// 与 Bash 手册 “Shell Parameter Expansion” 一节的行为对照，
+    /// 期望值均可在 bash 中以相同的变量复现。
+    #[test]
+    fn test_bash_conformance() {
+        let env = [
+            ("SET", "value"),
+            ("EMPTY", ""),
+            ("PATHNAME", "/usr/local/lib/libfoo.so.1"),
+            ("FILE", "archive.tar.gz"),
+            ("STR", "01234567890abcdefgh"),
+            ("WORDS", "foo bar foo baz"),
+            ("UNI", "中文字符串"),
+            ("OTHER", "other"),
+        ];
+        #[rustfmt::skip]
+        let table = [
+            // use default values
+            ("${SET:-d}", "value"),
+            ("${EMPTY:-d}", "d"),
+            ("${UNSET:-d}", "d"),
+            ("${SET-d}", "value"),
+            ("${EMPTY-d}", ""),
+            ("${UNSET-d}", "d"),
+            ("${UNSET:-$OTHER}", "other"),
+            ("${UNSET:-${EMPTY:-nested}}", "nested"),
+            ("${UNSET:-a\\}b}", "a}b"),
+            // assign default values
+            ("${UNSET:=x}-$UNSET", "x-x"),
+            ("${EMPTY:=x}-$EMPTY", "x-x"),
+            ("${EMPTY=x}-$EMPTY", "-"),
+            ("${SET:=x}", "value"),
+            // display error if null or unset
+            ("${SET:?msg}", "value"),
+            ("${EMPTY?msg}", ""),
+            // use alternate value
+            ("${SET:+alt}", "alt"),
+            ("${EMPTY:+alt}", ""),
+            ("${EMPTY+alt}", "alt"),
+            ("${UNSET+alt}", ""),
+            ("${SET:+$OTHER}", "other"),
+            // length
+            ("${#SET}", "5"),
+            ("${#EMPTY}", "0"),
+            ("${#UNSET}", "0"),
+            ("${#UNI}", "5"),
+            // remove matching prefix / suffix
+            ("${PATHNAME#*/}", "usr/local/lib/libfoo.so.1"),
+            ("${PATHNAME##*/}", "libfoo.so.1"),
+            ("${PATHNAME%/*}", "/usr/local/lib"),
+            ("${PATHNAME%%/lib*}", "/usr/local"),
+            ("${FILE%.*}", "archive.tar"),
+            ("${FILE%%.*}", "archive"),
+            ("${FILE#*.}", "tar.gz"),
+            ("${FILE##*.}", "gz"),
+            ("${FILE#nomatch}", "archive.tar.gz"),
+            ("${FILE%[.]gz}", "archive.tar"),
+            ("${FILE#\\*}", "archive.tar.gz"),
+            ("${UNI#中文}", "字符串"),
+            // pattern substitution
+            ("${WORDS/foo/qux}", "qux bar foo baz"),
+            ("${WORDS//foo/qux}", "qux bar qux baz"),
+            ("${WORDS//o}", "f bar f baz"),
+            ("${WORDS/#foo/qux}", "qux bar foo baz"),
+            ("${WORDS/#bar/qux}", "foo bar foo baz"),
+            ("${WORDS/%baz/qux}", "foo bar foo qux"),
+            ("${WORDS/b*/X}", "foo X"),
+            ("${WORDS//[ab]/_}", "foo __r foo __z"),
+            ("${WORDS/foo/$OTHER}", "other bar foo baz"),
+            ("${PATHNAME//\\//:}", ":usr:local:lib:libfoo.so.1"),
+            ("${WORDS/}", "foo bar foo baz"),
+            // substring expansion
+            ("${STR:7}", "7890abcdefgh"),
+            ("${STR:7:0}", ""),
+            ("${STR:7:2}", "78"),
+            ("${STR:7:-2}", "7890abcdef"),
+            ("${STR: -7}", "bcdefgh"),
+            ("${STR: -7:0}", ""),
+            ("${STR: -7:2}", "bc"),
+            ("${STR: -7:-2}", "bcdef"),
+            ("${STR:(-7):2}", "bc"),
+            ("${STR:30}", ""),
+            ("${STR: -30}", ""),
+            ("${UNI:1:2}", "文字"),
+            ("${SET:0:100}", "value"),
+        ];
+        for (input, expected) in table {
+            assert_eq!(expand_env(input, &env), expected, "input: {input}");
+        }
+    }
+
+    /// 与 zsh 手册 “Modifiers” 与 “Parameter Expansion Flags” 两节的行为对照
+    #[test]
+    fn test_zsh_conformance() {
+        let env = [
+            ("PATHNAME", "/usr/local/lib/libfoo.so.1"),
+            ("FILE", "archive.tar.gz"),
+            ("DOTFILE", "/home/me/.zshrc"),
+            ("ROOTED", "/bin"),
+            ("WORDS", "hello wORLD-foo"),
+            ("REL", "a/./b/../c"),
+        ];
+        let options = ExpandOptions {
+            dialect: Dialect::Zsh,
+            ..Default::default()
+        };
+        #[rustfmt::skip]
+        let table = [
+            ("${PATHNAME:h}", "/usr/local/lib"),
+            ("${PATHNAME:h:h}", "/usr/local"),
+            ("${ROOTED:h}", "/"),
+            ("${FILE:h}", "."),
+            ("${PATHNAME:t}", "libfoo.so.1"),
+            ("${FILE:r}", "archive.tar"),
+            ("${FILE:r:r}", "archive"),
+            ("${FILE:e}", "gz"),
+            ("${DOTFILE:e}", ""),
+            ("${DOTFILE:r}", "/home/me/.zshrc"),
+            ("${PATHNAME:t:r:u}", "LIBFOO.SO"),
+            ("${WORDS:l}", "hello world-foo"),
+            ("${(U)WORDS}", "HELLO WORLD-FOO"),
+            ("${(L)WORDS}", "hello world-foo"),
+            ("${(C)WORDS}", "Hello World-Foo"),
+            ("${(U)PATHNAME:t}", "LIBFOO.SO.1"),
+            ("${(U)UNSET:-fallback}", "FALLBACK"),
+            ("${FILE:0:7}", "archive"),
+        ];
+        for (input, expected) in table {
+            assert_eq!(try_expand_with(input, &env, &options).unwrap(), expected, "input: {input}");
+        }
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(
+            try_expand_with("${REL:a}", &env, &options).unwrap(),
+            format!("{}/a/c", cwd.display())
+        );
+        assert_eq!(
+            try_expand_with("${ROOTED:A}", &env, &options).unwrap(),
+            std::fs::canonicalize("/bin").unwrap().display().to_string()
+        );
+        // Bash 方言中 `:h` 是无效的子串偏移
+        assert!(try_expand_with("${PATHNAME:h}", &env, &ExpandOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_alternative_syntaxes() {
+        let env = [
+            ("USER", "alice"),
+            ("HOME", "C:\\Users\\alice"),
+            ("CONFIG", "%HOME%\\.config"),
+            ("EMPTY", ""),
+        ];
+        let syntax = |syntax: Syntax| ExpandOptions {
+            syntax,
+            ..Default::default()
+        };
+        #[rustfmt::skip]
+        let table = [
+            (Syntax::BATCH, "%USER%@%HOME%", "alice@C:\\Users\\alice"),
+            (Syntax::BATCH, "100%% %USER", "100% %USER"),
+            (Syntax::BATCH, "%MISSING%|%EMPTY%|%not a var%", "||%not a var%"),
+            (Syntax::BATCH, "$USER `cmd`", "$USER `cmd`"),
+            (Syntax::POWERSHELL, "$env:USER $Env:HOME $envUSER", "alice C:\\Users\\alice $envUSER"),
+            (Syntax::POWERSHELL, "`$env:USER $$", "$env:USER $$"),
+            (Syntax::MUSTACHE, "{{USER}} {{  USER  }} {{ not valid }} {{USER", "alice alice {{ not valid }} {{USER"),
+            (Syntax::MUSTACHE, "\\{{ USER }} {USER}", "{{ USER }} {USER}"),
+            (Syntax::DOLLAR | Syntax::POWERSHELL, "$env:USER ${USER} $$env:USER", "alice alice $env:USER"),
+            (Syntax::DOLLAR | Syntax::BATCH, "${USER:-%HOME%} %USER%", "alice alice"),
+            (Syntax::DOLLAR | Syntax::BATCH, "${MISSING:-%USER%}", "%USER%"),
+        ];
+        for (syntax_set, input, expected) in table {
+            assert_eq!(
+                try_expand_with(input, &env, &syntax(syntax_set)).unwrap(),
+                expected,
+                "input: {input}"
+            );
+        }
+        let batch = syntax(Syntax::BATCH);
+        assert_eq!(
+            try_expand_recursive_with("%CONFIG%", &env, &batch).unwrap(),
+            "C:\\Users\\alice\\.config"
+        );
+        let strict = ExpandOptions { strict: true, ..batch };
+        assert_eq!(
+            try_expand_with("x %MISSING%", &env, &strict),
+            Err(ExpandError::UndefinedVariable {
+                name: "MISSING".into(),
+                span: 2..11
+            })
+        );
+    }
+}
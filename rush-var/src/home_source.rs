@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+/// 用户主目录查找源 trait，用于 `~user` 形式的波浪号展开。
+/// Trait for abstracting user home directory lookup.
+///
+/// `~` 本身取自环境变量 `HOME`，`~+` / `~-` 取自 `PWD` / `OLDPWD`，只有 `~user` 会查询该 trait。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::{ExpandOptions, try_expand_with};
+/// let env = [("HOME", "/home/me")];
+/// let homes = [("alice", "/home/alice")];
+/// let options = ExpandOptions { home: Some(&homes), ..Default::default() };
+/// assert_eq!(try_expand_with("~/bin:~alice/bin", &env, &options).unwrap(), "/home/me/bin:/home/alice/bin");
+/// ```
+pub trait HomeSource {
+    /// 获取指定用户的主目录，如果用户不存在则返回 None。
+    fn home_dir(&self, user: &str) -> Option<String>;
+}
+
+/// 为任意已实现 HomeSource 的类型的引用自动实现 HomeSource
+impl<T: HomeSource + ?Sized> HomeSource for &T {
+    fn home_dir(&self, user: &str) -> Option<String> {
+        (**self).home_dir(user)
+    }
+}
+
+/// HashMap（用户名 -> 主目录）作为查找源
+impl HomeSource for HashMap<String, String> {
+    fn home_dir(&self, user: &str) -> Option<String> {
+        self.get(user).cloned()
+    }
+}
+
+/// 切片 &[(&str, &str)] 作为查找源，适用于测试中 mock 用户
+impl<'a> HomeSource for &'a [(&'a str, &'a str)] {
+    fn home_dir(&self, user: &str) -> Option<String> {
+        self.iter().find(|(name, _)| *name == user).map(|(_, home)| (*home).to_owned())
+    }
+}
+
+/// 定长数组 [(&str, &str); N] 作为查找源
+impl<const N: usize> HomeSource for [(&str, &str); N] {
+    fn home_dir(&self, user: &str) -> Option<String> {
+        self.iter().find(|(name, _)| *name == user).map(|(_, home)| (*home).to_owned())
+    }
+}
+
+/// 系统用户数据库：当前用户取 `HOME`，其余用户从 `/etc/passwd` 读取
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemHome;
+
+impl HomeSource for SystemHome {
+    fn home_dir(&self, user: &str) -> Option<String> {
+        if std::env::var("USER").is_ok_and(|current| current == user) {
+            return std::env::var("HOME").ok();
+        }
+        // name:password:uid:gid:gecos:home:shell
+        let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+        passwd
+            .lines()
+            .map(|line| line.split(':').collect::<Vec<_>>())
+            .find(|fields| fields.len() >= 6 && fields[0] == user)
+            .map(|fields| fields[5].to_string())
+    }
+}
+
+/// 闭包适配器，允许以自定义函数/闭包方式提供主目录查找逻辑。
+pub struct FnHomeSource<F>(pub F);
+
+impl<F> HomeSource for FnHomeSource<F>
+where
+    for<'a> F: Fn(&'a str) -> Option<String>,
+{
+    fn home_dir(&self, user: &str) -> Option<String> {
+        self.0(user)
+    }
+}
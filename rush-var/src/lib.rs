@@ -1,10 +1,16 @@
 //! # rush-var —— Bash风格环境变量插值库
 //!
-//! 支持 `$VAR`、`${VAR}`、`${VAR:-default}`、`$$`（字面$）等语法，支持递归插值，适配多种环境变量源：
+//! 支持 `$VAR`、`${VAR}`、`$$`（字面$）以及 Bash 的全部参数展开操作（`${VAR:-default}`、`${VAR:+alt}`、
+//! `${VAR:=x}`、`${VAR:?msg}`、`${#VAR}`、`${VAR#prefix}`、`${VAR%suffix}`、`${VAR/pat/rep}`、`${VAR:offset:len}`），
+//! 支持递归插值，适配多种环境变量源：
 //!
 //! - [`HashMap<String, String>`], [`BTreeMap`], 切片对 (`&[(&str, &str)]`)
 //! - 自定义闭包 [`FnEnvSource`]（例如连接数据库、远程环境服务等）
 //! - 组合多个源 [`EnvSourceChain`]，优先从主源读取，回退到备用源
+//! - 分层可写的 [`env_stack::EnvStack`]，支持 `set`/`unset` 与作用域，在内存中模拟 shell 环境
+//! - dotenv 文件 [`dotenv::Dotenv`]，支持引号、转义、`export` 前缀、注释、多行值与条目间插值
+//!
+//! 开启 `serde` feature 后，`serde` 模块提供反序列化时展开变量的 `Expanded<T>` 与保留原文的 `Template`。
 //!
 //! ## 基础用法
 //!
@@ -48,7 +54,36 @@
 //! assert_eq!(expand_env_recursive("val=$A", &env), "val=123");
 //! ```
 //!
-//! 最大递归深度限制为 8 层，以防止无限循环。
+//! 每个变量的值在代入前单独展开，已展开的结果不会再被扫描，因此值中的 `$$` 只会变成一个字面 `$`。
+//! 循环引用（如 `A -> B -> A`）与超过 [`ExpandOptions::max_depth`]（默认 8 层）的嵌套保留原值，
+//! 使用 [`try_expand_recursive`] 时则返回带有完整引用路径的错误。
+//!
+//! ## 波浪号展开
+//!
+//! 默认不处理 `~`；在 [`ExpandOptions::home`] 中提供 [`home_source::HomeSource`] 后，
+//! 词首或 `:` 之后的 `~`、`~/`、`~user`、`~+`、`~-` 会被展开：
+//!
+//! ```rust
+//! use rush_var::home_source::SystemHome;
+//! use rush_var::{ExpandOptions, try_expand_with};
+//! let env = [("HOME", "/home/me"), ("PWD", "/work")];
+//! let options = ExpandOptions { home: Some(&SystemHome), ..Default::default() };
+//! assert_eq!(try_expand_with("~/.cargo/env a~b ~+", &env, &options).unwrap(), "/home/me/.cargo/env a~b /work");
+//! ```
+//!
+//! ## 错误诊断
+//!
+//! `expand_env` 系列从不失败；需要定位配置错误时使用 `try_expand` 系列，
+//! 返回带有字节区间的 [`error::ExpandError`]：
+//!
+//! ```rust
+//! use rush_var::try_expand;
+//! let env = [("FOO", "bar")];
+//! let input = "${FOO:?must be set}/${BAR";
+//! let err = try_expand(input, &env).unwrap_err();
+//! assert_eq!(err.span(), 20..25);
+//! println!("{err}\n{}", err.annotate(input));
+//! ```
 //!
 //! ## 用于 std::env::vars()
 //!
@@ -58,9 +93,85 @@
 //! assert_eq!(expand_env_vars("$FOO world"), "hello world");
 //! ```
 
+pub mod ast;
+pub mod command;
+pub mod dotenv;
 pub mod env_source;
+pub mod env_stack;
+pub mod error;
+mod expand;
+pub mod home_source;
+pub mod os_env_source;
+mod pattern;
+#[cfg(feature = "serde")]
+pub mod serde;
 
+use crate::ast::{Dialect, Node, Syntax};
+use crate::command::CommandRunner;
 use crate::env_source::EnvSource;
+use crate::error::ExpandError;
+use crate::expand::Expander;
+use crate::home_source::HomeSource;
+use crate::os_env_source::{Encoded, OsEnvSource};
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Debug, Formatter};
+
+/// 展开选项，用于 `try_expand` 系列函数。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::error::ExpandError;
+/// use rush_var::{ExpandOptions, try_expand_with};
+/// let env = [("FOO", "bar")];
+/// let options = ExpandOptions { strict: true, ..Default::default() };
+/// assert_eq!(try_expand_with("${MISSING:-x}", &env, &options).unwrap(), "x");
+/// assert_eq!(
+///     try_expand_with("$FOO/$MISSING", &env, &options),
+///     Err(ExpandError::UndefinedVariable { name: "MISSING".into(), span: 5..13 })
+/// );
+/// ```
+#[derive(Clone)]
+pub struct ExpandOptions<'h> {
+    /// 严格模式（类似 `set -u`）：引用未定义的变量时报错，`${VAR:-x}`、`${VAR:+x}` 等不受影响
+    pub strict: bool,
+    /// 递归展开时变量值嵌套解析的最大层数
+    pub max_depth: usize,
+    /// 开启波浪号展开，`~user` 通过它查找主目录；变量值中的 `~` 不会展开
+    pub home: Option<&'h dyn HomeSource>,
+    /// 命令替换的执行器，为 None 时 `$(...)` 与反引号原样保留
+    pub runner: Option<&'h dyn CommandRunner>,
+    /// 语法方言，[`Dialect::Zsh`] 时支持 `${VAR:h}` 等修饰符与 `${(U)VAR}` 等参数标志
+    pub dialect: Dialect,
+    /// 识别的变量语法，默认只有 `$VAR`，可以组合 [`Syntax::BATCH`] 等
+    pub syntax: Syntax,
+}
+
+impl Default for ExpandOptions<'_> {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            max_depth: 8,
+            home: None,
+            runner: None,
+            dialect: Dialect::Bash,
+            syntax: Syntax::DOLLAR,
+        }
+    }
+}
+
+impl Debug for ExpandOptions<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpandOptions")
+            .field("strict", &self.strict)
+            .field("max_depth", &self.max_depth)
+            .field("tilde", &self.home.is_some())
+            .field("substitute", &self.runner.is_some())
+            .field("dialect", &self.dialect)
+            .field("syntax", &self.syntax)
+            .finish()
+    }
+}
 
 pub fn expand_env_vars(input: &str) -> String {
     let vars = std::env::vars();
@@ -68,24 +179,65 @@ pub fn expand_env_vars(input: &str) -> String {
 }
 
 pub fn expand_env_recursive(input: &str, env: &impl EnvSource) -> String {
-    const MAX_EXPAND_DEPTH: usize = 8;
-    fn inner(s: &str, env: &impl EnvSource, depth: usize) -> String {
-        if depth >= MAX_EXPAND_DEPTH {
-            return s.to_string();
-        }
-        let expanded = expand_env(s, env);
-        if expanded.contains('$') && expanded != s {
-            inner(&expanded, env, depth + 1)
-        } else {
-            expanded
-        }
-    }
-    inner(input, env, 0)
+    Expander::new(env, &ExpandOptions::default(), false)
+        .recursive()
+        .expand(input)
+        .unwrap_or_else(|_| unreachable!("宽松模式下展开不会失败"))
+}
+
+/// [`expand_env_vars`] 的可失败版本
+pub fn try_expand_vars(input: &str) -> Result<String, ExpandError> {
+    let vars = std::env::vars();
+    try_expand_recursive(input, &vars)
+}
+
+/// [`expand_env_recursive`] 的可失败版本
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::error::ExpandError;
+/// use rush_var::try_expand_recursive;
+/// let env = [("A", "$B"), ("B", "${C}"), ("C", "$A"), ("PRICE", "$$100")];
+/// assert_eq!(try_expand_recursive("$PRICE", &env).unwrap(), "$100");
+/// assert_eq!(
+///     try_expand_recursive("x=$A", &env),
+///     Err(ExpandError::RecursionCycle { path: vec!["A".into(), "B".into(), "C".into(), "A".into()], span: 2..4 })
+/// );
+/// ```
+pub fn try_expand_recursive(input: &str, env: &impl EnvSource) -> Result<String, ExpandError> {
+    try_expand_recursive_with(input, env, &ExpandOptions::default())
+}
+
+/// 递归展开，变量值中的错误统一标注在最外层的引用处。
+pub fn try_expand_recursive_with(input: &str, env: &impl EnvSource, options: &ExpandOptions) -> Result<String, ExpandError> {
+    Expander::new(env, options, true).recursive().expand(input)
+}
+
+/// [`expand_env`] 的可失败版本：未闭合的 `${`、无法识别的 `${...}` 与 `${VAR:?msg}` 均返回错误。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::error::ExpandError;
+/// use rush_var::try_expand;
+/// let env = [("FOO", "bar")];
+/// assert_eq!(try_expand("$FOO/bin", &env).unwrap(), "bar/bin");
+/// assert_eq!(
+///     try_expand("${HOME:?not set}", &env),
+///     Err(ExpandError::ParameterError { name: "HOME".into(), message: "not set".into(), span: 0..16 })
+/// );
+/// ```
+pub fn try_expand(input: &str, env: &impl EnvSource) -> Result<String, ExpandError> {
+    try_expand_with(input, env, &ExpandOptions::default())
+}
+
+pub fn try_expand_with(input: &str, env: &impl EnvSource, options: &ExpandOptions) -> Result<String, ExpandError> {
+    Expander::new(env, options, true).expand(input)
 }
 
 /// Bash 风格环境变量插值主函数。
 ///
-/// 支持 $VAR、${VAR}、${VAR:-default}、$$（字面$），适配多种环境变量源。
+/// 支持 $VAR、${VAR}、$$（字面$）以及 Bash 的 `${VAR:-default}`、`${VAR:+alt}`、`${#VAR}`、
+/// `${VAR#prefix}`、`${VAR/pat/rep}`、`${VAR:offset:len}` 等参数展开，适配多种环境变量源。
 ///
 /// # 用法示例
 /// ```rust
@@ -94,65 +246,104 @@ pub fn expand_env_recursive(input: &str, env: &impl EnvSource) -> String {
 /// assert_eq!(expand_env("$FOO/bin", &env), "bar/bin");
 /// assert_eq!(expand_env("${BAR:-default}/lib", &env), "default/lib");
 /// ```
-pub fn expand_env(input: &str, env: &impl EnvSource) -> String {
-    let mut result = String::new();
-    let mut chars = input.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        if c == '$' {
-            match chars.peek() {
-                Some('$') => {
-                    chars.next(); // consume second $
-                    result.push('$');
-                }
-                Some('{') => {
-                    chars.next(); // consume '{'
-                    let mut key = String::new();
-                    let mut default = None;
-                    let mut in_default = false;
-                    while let Some(&ch) = chars.peek() {
-                        if ch == '}' {
-                            chars.next(); // consume '}'
-                            break;
-                        } else if ch == ':' && chars.clone().nth(1) == Some('-') {
-                            chars.next();
-                            chars.next(); // consume :-
-                            in_default = true;
-                        } else {
-                            if in_default {
-                                default.get_or_insert(String::new()).push(ch);
-                            } else {
-                                key.push(ch);
-                            }
-                            chars.next();
-                        }
-                    }
-                    let val = env.get(&key).or(default.as_ref().cloned()).unwrap_or_default();
-                    result.push_str(&val);
-                }
-                Some(ch) if ch.is_alphanumeric() || *ch == '_' => {
-                    let mut key = String::new();
-                    while let Some(&ch) = chars.peek() {
-                        if ch.is_alphanumeric() || ch == '_' {
-                            key.push(ch);
-                            chars.next();
-                        } else {
-                            break;
-                        }
-                    }
-                    let val = env.get(&key).unwrap_or_default();
-                    result.push_str(&val);
-                }
-                _ => {
-                    result.push('$');
-                }
-            }
-        } else {
-            result.push(c);
+///
+/// 不含 `$` 与反引号的输入直接借用返回，不会分配内存。
+pub fn expand_env<'a>(input: &'a str, env: &impl EnvSource) -> Cow<'a, str> {
+    expand_env_with(input, env, &ExpandOptions::default())
+}
+
+/// 按 `options` 展开（例如启用 [`Syntax::BATCH`] 等其他变量语法），与 [`expand_env`] 一样从不失败，`strict` 不生效
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::ast::Syntax;
+/// use rush_var::{ExpandOptions, expand_env_with};
+/// let env = [("PATH", "/usr/bin")];
+/// let options = ExpandOptions { syntax: Syntax::POWERSHELL, ..Default::default() };
+/// assert_eq!(expand_env_with("$env:PATH;$HOME `$env:PATH", &env, &options), "/usr/bin;$HOME $env:PATH");
+/// ```
+pub fn expand_env_with<'a>(input: &'a str, env: &impl EnvSource, options: &ExpandOptions) -> Cow<'a, str> {
+    let mut expander = Expander::new(env, options, false);
+    if expander.is_plain(input) {
+        return Cow::Borrowed(input);
+    }
+    Cow::Owned(expander.expand(input).unwrap_or_else(|_| unreachable!("宽松模式下展开不会失败")))
+}
+
+/// 与 [`expand_env`] 相同，但把结果逐段写入 `out`，适合直接写入文件或已有的缓冲区。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::expand_into;
+/// let env = [("FOO", "bar")];
+/// let mut out = String::from("> ");
+/// expand_into("$FOO/bin", &env, &mut out).unwrap();
+/// assert_eq!(out, "> bar/bin");
+/// ```
+pub fn expand_into(input: &str, env: &impl EnvSource, out: &mut impl fmt::Write) -> fmt::Result {
+    let options = ExpandOptions::default();
+    let mut expander = Expander::new(env, &options, false);
+    if expander.is_plain(input) {
+        return out.write_str(input);
+    }
+    let mut buf = String::new();
+    for node in ast::parse_lenient(input, options.dialect, options.syntax) {
+        if let Node::Literal(text) = node {
+            out.write_str(text)?;
+            continue;
         }
+        buf.clear();
+        expander
+            .eval_into(std::slice::from_ref(&node), &mut buf)
+            .unwrap_or_else(|_| unreachable!("宽松模式下展开不会失败"));
+        out.write_str(&buf)?;
     }
+    Ok(())
+}
+
+/// 在 `OsStr` 上展开，变量值来自 [`OsEnvSource`]，非 UTF-8 的字节原样保留。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::expand_env_os;
+/// use std::collections::HashMap;
+/// use std::ffi::{OsStr, OsString};
+/// let mut env = HashMap::new();
+/// env.insert("DIR".to_string(), OsString::from("/data"));
+/// assert_eq!(expand_env_os(OsStr::new("${DIR}/file"), &env), "/data/file");
+/// ```
+pub fn expand_env_os(input: &OsStr, env: &impl OsEnvSource) -> OsString {
+    let encoded = os_env_source::encode(input);
+    let expanded = Expander::new(&Encoded(env), &ExpandOptions::default(), false)
+        .expand(&encoded)
+        .unwrap_or_else(|_| unreachable!("宽松模式下展开不会失败"));
+    os_env_source::decode(&expanded)
+}
+
+/// [`try_expand_with`] 的 `OsStr` 版本，错误中的 `span` 为原始输入中的字节区间
+pub fn try_expand_os_with(input: &OsStr, env: &impl OsEnvSource, options: &ExpandOptions) -> Result<OsString, ExpandError> {
+    try_expand_os_impl(input, env, options, false)
+}
 
-    result
+/// [`try_expand_recursive_with`] 的 `OsStr` 版本
+pub fn try_expand_os_recursive_with(input: &OsStr, env: &impl OsEnvSource, options: &ExpandOptions) -> Result<OsString, ExpandError> {
+    try_expand_os_impl(input, env, options, true)
+}
+
+fn try_expand_os_impl(input: &OsStr, env: &impl OsEnvSource, options: &ExpandOptions, recursive: bool) -> Result<OsString, ExpandError> {
+    let encoded = os_env_source::encode(input);
+    let env = Encoded(env);
+    let mut expander = Expander::new(&env, options, true);
+    if recursive {
+        expander = expander.recursive();
+    }
+    match expander.expand(&encoded) {
+        Ok(expanded) => Ok(os_env_source::decode(&expanded)),
+        Err(error) => {
+            let span = os_env_source::original_span(&encoded, error.span());
+            Err(error.with_span(span))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -289,7 +480,169 @@ mod tests {
         let mut env = HashMap::new();
         env.insert("LOOP".into(), "$LOOP".into());
         let res = expand_env_recursive("start:$LOOP:end", &env);
-        // 最多递归8次，最后返回原样
-        assert!(res.contains("$LOOP"));
+        // 循环引用保留原值
+        assert_eq!(res, "start:$LOOP:end");
+    }
+
+    #[test]
+    fn test_try_expand_errors() {
+        let env = [("FOO", "bar"), ("EMPTY", "")];
+        assert_eq!(try_expand("${FOO}/${BAR:-x}", &env).unwrap(), "bar/x");
+        assert_eq!(
+            try_expand("a ${FOO BAR} b", &env),
+            Err(ExpandError::BadSubstitution { span: 2..12 })
+        );
+        assert_eq!(
+            try_expand("x${EMPTY:?}", &env),
+            Err(ExpandError::ParameterError {
+                name: "EMPTY".into(),
+                message: String::new(),
+                span: 1..11
+            })
+        );
+        // 宽松模式下未闭合的 `${` 照常展开
+        assert_eq!(expand_env("a ${FOO", &env), "a bar");
+    }
+
+    #[test]
+    fn test_try_expand_strict() {
+        let env = [("FOO", "bar")];
+        let options = ExpandOptions {
+            strict: true,
+            ..Default::default()
+        };
+        assert_eq!(try_expand_with("$FOO${BAR-}${BAR:+x}", &env, &options).unwrap(), "bar");
+        assert_eq!(
+            try_expand_with("$FOO ${BAR#x}", &env, &options),
+            Err(ExpandError::UndefinedVariable {
+                name: "BAR".into(),
+                span: 5..13
+            })
+        );
+        assert_eq!(try_expand("$BAR", &env).unwrap(), "");
+    }
+
+    #[test]
+    fn test_recursive_keeps_escaped_dollar() {
+        let env = [("PRICE", "$$100"), ("WRAP", "[$PRICE]")];
+        assert_eq!(expand_env_recursive("$$1 $WRAP", &env), "$1 [$100]");
+        assert_eq!(expand_env_recursive("${WRAP#[}", &env), "$100]");
+    }
+
+    #[test]
+    fn test_try_expand_recursion_errors() {
+        let env = [("LOOP", "x$LOOP"), ("A", "${B:-$C}"), ("B", ""), ("C", "$A")];
+        assert_eq!(
+            try_expand_recursive("[$LOOP]", &env),
+            Err(ExpandError::RecursionCycle {
+                path: vec!["LOOP".into(), "LOOP".into()],
+                span: 1..6
+            })
+        );
+        let err = try_expand_recursive("${A}", &env).unwrap_err();
+        assert_eq!(err.to_string(), "variable cycle A -> C -> A at 0..4");
+
+        let env = [("A", "$B"), ("B", "$C"), ("C", "$D"), ("D", "done")];
+        assert_eq!(try_expand_recursive("$A", &env).unwrap(), "done");
+        let options = ExpandOptions {
+            max_depth: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            try_expand_recursive_with("- $A", &env, &options),
+            Err(ExpandError::RecursionLimit { limit: 2, span: 2..4 })
+        );
+    }
+
+    #[test]
+    fn test_tilde_expansion() {
+        let env = [("HOME", "/home/me"), ("PWD", "/work"), ("OLDPWD", "/prev"), ("V", "~/x")];
+        let homes = [("alice", "/home/alice"), ("bob.smith", "/home/bob")];
+        let options = ExpandOptions {
+            home: Some(&homes),
+            ..Default::default()
+        };
+        let expand = |input: &str| try_expand_with(input, &env, &options).unwrap();
+        assert_eq!(expand("~"), "/home/me");
+        assert_eq!(expand("~/bin:~alice/bin:~bob.smith"), "/home/me/bin:/home/alice/bin:/home/bob");
+        assert_eq!(expand("cd ~+ ~-"), "cd /work /prev");
+        // 非词首、未知用户与变量值中的 ~ 均保留
+        assert_eq!(expand("a~ ~nobody/x ~$V"), "a~ ~nobody/x ~~/x");
+        assert_eq!(expand("${MISSING:-~/default}"), "/home/me/default");
+        assert_eq!(try_expand_recursive_with("$V", &env, &options).unwrap(), "~/x");
+        // 默认不开启
+        assert_eq!(try_expand("~/bin", &env).unwrap(), "~/bin");
+    }
+
+    #[test]
+    fn test_command_substitution() {
+        let env = [("JAVA_VERSION", "17")];
+        let input = "$(/usr/libexec/java_home -v ${JAVA_VERSION})/bin:`uname -s`";
+        // 默认原样保留给 shell
+        assert_eq!(expand_env(input, &env), input);
+        assert_eq!(expand_env("$((1 + (2 * 3))) $(echo ')')", &env), "$((1 + (2 * 3))) $(echo ')')");
+
+        let runner = command::MockRunner::default()
+            .with("/usr/libexec/java_home -v 17", "/opt/jdk-17")
+            .with("uname -s", "Darwin");
+        let options = ExpandOptions {
+            runner: Some(&runner),
+            ..Default::default()
+        };
+        assert_eq!(try_expand_with(input, &env, &options).unwrap(), "/opt/jdk-17/bin:Darwin");
+        assert_eq!(try_expand_with("${UNSET:-`uname -s`}", &env, &options).unwrap(), "Darwin");
+        assert_eq!(
+            try_expand_with("x $(whoami)", &env, &options),
+            Err(ExpandError::CommandFailed {
+                command: "whoami".into(),
+                message: "unexpected command: whoami".into(),
+                span: 2..11
+            })
+        );
+        assert_eq!(
+            try_expand_with("a `uname", &env, &options),
+            Err(ExpandError::UnterminatedSubstitution { span: 2..8 })
+        );
+        assert_eq!(expand_env("a $(echo", &env), "a $(echo");
+    }
+
+    #[test]
+    fn test_plain_input_is_borrowed() {
+        let env = [("FOO", "bar")];
+        assert!(matches!(expand_env("no variables ~ here", &env), Cow::Borrowed(_)));
+        assert!(matches!(expand_env("$FOO", &env), Cow::Owned(_)));
+    }
+
+    #[test]
+    fn test_expand_into_matches_expand_env() {
+        let env = [("FOO", "bar"), ("EMPTY", "")];
+        for input in ["", "plain", "$FOO/${EMPTY:-x}/${#FOO}/$$/${FOO/a/A}", "a ${FOO", "`cmd` $(cmd)"] {
+            let mut out = String::new();
+            expand_into(input, &env, &mut out).unwrap();
+            assert_eq!(out, expand_env(input, &env));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_os_keeps_invalid_bytes() {
+        use std::collections::HashMap;
+        use std::os::unix::ffi::OsStrExt;
+        let mut env = HashMap::new();
+        env.insert("DIR".to_string(), OsString::from(OsStr::from_bytes(b"/data/caf\xe9")));
+        env.insert("NESTED".to_string(), OsString::from("${DIR}/sub"));
+        let input = OsStr::from_bytes(b"$DIR/\xff${MISSING:-x}");
+        assert_eq!(expand_env_os(input, &env).as_bytes(), b"/data/caf\xe9/\xffx");
+        let options = ExpandOptions::default();
+        assert_eq!(
+            try_expand_os_recursive_with(OsStr::new("$NESTED"), &env, &options)
+                .unwrap()
+                .as_bytes(),
+            b"/data/caf\xe9/sub"
+        );
+        assert_eq!(
+            try_expand_os_with(OsStr::from_bytes(b"\xff\xfe ${DIR"), &env, &options),
+            Err(ExpandError::UnterminatedBrace { span: 3..8 })
+        );
     }
 }
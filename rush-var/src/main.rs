@@ -0,0 +1,266 @@
+use clap::{Parser, ValueEnum};
+use rush_var::ast::{self, Dialect, Node};
+use rush_var::dotenv::Dotenv;
+use rush_var::env_source::{EnvSource, FnEnvSource};
+use rush_var::env_stack::EnvStack;
+use rush_var::error::ExpandError;
+use rush_var::{ExpandOptions, try_expand_with};
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// 类似 envsubst：按 rush 的规则展开标准输入或文件中的变量，结果写到标准输出
+#[derive(Debug, Parser)]
+#[command(name = "rush-var", version, about)]
+struct Cli {
+    /// 输入文件，依次展开后拼接输出；省略或为 `-` 时读取标准输入
+    files: Vec<PathBuf>,
+    /// 引用未定义的变量时报错（类似 `set -u`）
+    #[arg(long)]
+    strict: bool,
+    /// 只展开列出的变量，其余引用原样保留
+    #[arg(long, value_name = "VAR,VAR", value_delimiter = ',')]
+    only: Option<Vec<String>>,
+    /// 从 dotenv 文件读取变量，可重复指定，后面的文件优先于前面的文件与进程环境
+    #[arg(long = "env-file", value_name = "PATH")]
+    env_files: Vec<PathBuf>,
+    /// 解析所遵循的 shell 方言，zsh 额外支持 `${VAR:h}` 等修饰符与 `${(U)VAR}` 等参数标志
+    #[arg(long, value_enum, default_value_t = DialectArg::Bash)]
+    dialect: DialectArg,
+    /// 不展开，只按首次出现的顺序列出输入引用的变量名，每行一个
+    #[arg(long)]
+    list_vars: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DialectArg {
+    Bash,
+    Zsh,
+}
+
+impl From<DialectArg> for Dialect {
+    fn from(dialect: DialectArg) -> Self {
+        match dialect {
+            DialectArg::Bash => Dialect::Bash,
+            DialectArg::Zsh => Dialect::Zsh,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match cli.run(&mut std::io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("rush-var: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+impl Cli {
+    fn run(&self, out: &mut impl Write) -> Result<(), String> {
+        let inputs = self.inputs()?;
+        if self.list_vars {
+            let mut vars = Vec::new();
+            for (_, input) in &inputs {
+                for name in ast::referenced_vars_dialect(input, self.dialect.into()) {
+                    if !vars.contains(&name) {
+                        vars.push(name);
+                    }
+                }
+            }
+            for name in vars {
+                writeln!(out, "{name}").map_err(|error| error.to_string())?;
+            }
+            return Ok(());
+        }
+        let env = self.env()?;
+        for (name, input) in &inputs {
+            let expanded = self.expand(input, &env).map_err(|error| describe(name, input, error))?;
+            out.write_all(expanded.as_bytes()).map_err(|error| error.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// 读取全部输入，返回 (显示名, 内容)
+    fn inputs(&self) -> Result<Vec<(String, String)>, String> {
+        let stdin = || {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map(|_| ("<stdin>".to_string(), input))
+                .map_err(|error| format!("<stdin>: {error}"))
+        };
+        if self.files.is_empty() {
+            return Ok(vec![stdin()?]);
+        }
+        self.files
+            .iter()
+            .map(|path| match path.to_str() {
+                Some("-") => stdin(),
+                _ => std::fs::read_to_string(path)
+                    .map(|input| (path.display().to_string(), input))
+                    .map_err(|error| format!("{}: {error}", path.display())),
+            })
+            .collect()
+    }
+
+    /// 进程环境在最底层，`--env-file` 依次叠加，文件中可以引用下层的变量
+    fn env(&self) -> Result<EnvStack<'static>, String> {
+        let mut env = EnvStack::new().with_source(std::env::vars());
+        for path in &self.env_files {
+            let dotenv = Dotenv::from_path_with(path, &env).map_err(|error| format!("{}: {error}", path.display()))?;
+            env.push_source(dotenv);
+        }
+        Ok(env)
+    }
+
+    /// 同 envsubst，变量的值原样插入，不会再次展开
+    fn expand(&self, input: &str, env: &impl EnvSource) -> Result<String, ExpandError> {
+        let options = ExpandOptions {
+            strict: self.strict,
+            dialect: self.dialect.into(),
+            ..Default::default()
+        };
+        let Some(only) = &self.only else {
+            return try_expand_with(input, env, &options);
+        };
+        let allowed = |name: &str| only.iter().any(|var| var == name);
+        // 列表之外的变量在允许的引用内部（如 `${A:-$B}` 中的 `$B`）视为未定义
+        let env = FnEnvSource(|name: &str| {
+            if allowed(name) {
+                env.get(name).map(|value| value.into_owned())
+            } else {
+                None
+            }
+        });
+        let mut output = String::with_capacity(input.len());
+        for node in ast::parse_dialect(input, options.dialect)? {
+            let (name, span) = match reference(&node) {
+                Some(reference) => reference,
+                None => {
+                    if let Node::Literal(text) = node {
+                        output.push_str(text);
+                    }
+                    continue;
+                }
+            };
+            let text = &input[span.clone()];
+            if name.is_some_and(|name| !allowed(name)) {
+                output.push_str(text);
+                continue;
+            }
+            let expanded = try_expand_with(text, &env, &options).map_err(|error| {
+                let inner = error.span();
+                error.with_span(span.start + inner.start..span.start + inner.end)
+            })?;
+            output.push_str(&expanded);
+        }
+        Ok(output)
+    }
+}
+
+/// 顶层节点引用的变量名与它在输入中的区间，字面文本返回 None
+fn reference<'a>(node: &Node<'a>) -> Option<(Option<&'a str>, Range<usize>)> {
+    match node {
+        Node::Literal(_) => None,
+        Node::Var { name, span, .. } | Node::Length { name, span } => Some((Some(name), span.clone())),
+        Node::Flagged { node, .. } => reference(node),
+        Node::Tilde { span, .. } | Node::Command { span, .. } | Node::Invalid { span } => Some((None, span.clone())),
+    }
+}
+
+/// 报告 `文件:行号: 错误`，并只标注出错的那一行
+fn describe(name: &str, input: &str, error: ExpandError) -> String {
+    let span = error.span();
+    let start = input[..span.start].rfind('\n').map_or(0, |newline| newline + 1);
+    let end = input[span.start..].find('\n').map_or(input.len(), |newline| span.start + newline);
+    let line = input[..span.start].matches('\n').count() + 1;
+    let annotated = error
+        .clone()
+        .with_span(span.start - start..span.end.min(end) - start)
+        .annotate(&input[start..end]);
+    format!("{name}:{line}: {error}\n{annotated}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli(args: &[&str]) -> Cli {
+        Cli::try_parse_from([&["rush-var"], args].concat()).unwrap()
+    }
+
+    #[test]
+    fn test_expand_options() {
+        let env = [("HOST", "localhost"), ("PORT", "8080"), ("FILE", "/etc/app.conf")];
+        let input = "url=http://$HOST:${PORT:-80} $$HOME ${#HOST} `date` ${FILE:t}";
+        assert_eq!(
+            "url=http://localhost:8080 $HOME 9 `date` ${FILE:t}",
+            cli(&["--only", "HOST,PORT"]).expand(input, &env).unwrap()
+        );
+        assert_eq!(
+            "url=http://localhost:8080 $HOME 9 `date` app.conf",
+            cli(&["--dialect", "zsh"]).expand(input, &env).unwrap()
+        );
+        assert!(cli(&[]).expand(input, &env).is_err());
+
+        let env = [("LIT", "a$HOME"), ("HOME", "/root")];
+        assert_eq!("a$HOME /root", cli(&[]).expand("$LIT $HOME", &env).unwrap());
+        assert_eq!("a$HOME $HOME", cli(&["--only", "LIT"]).expand("$LIT $HOME", &env).unwrap());
+
+        let error = cli(&["--strict", "--only", "A,B"])
+            .expand("$A\n  x=$B $C\n", &[("A", "1")])
+            .unwrap_err();
+        assert_eq!(
+            ExpandError::UndefinedVariable {
+                name: "B".into(),
+                span: 7..9
+            },
+            error
+        );
+        assert_eq!(
+            "input:2: undefined variable 'B' at 7..9\n  x=$B $C\n    ^^",
+            describe("input", "$A\n  x=$B $C\n", error)
+        );
+    }
+
+    #[test]
+    fn test_list_vars_and_env_files() {
+        let dir = std::env::temp_dir().join(format!("rush-var-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.tmpl"), "${NAME:-$USER_NAME} ${(U)NAME}\n").unwrap();
+        std::fs::write(dir.join("b.tmpl"), "$GREETING, $NAME\n").unwrap();
+        std::fs::write(dir.join("base.env"), "NAME=base\nGREETING=hi\n").unwrap();
+        std::fs::write(dir.join("local.env"), "NAME=\"${GREETING}-local\"\nQ='x$HOME'\nP='pa$$word'\n").unwrap();
+        std::fs::write(dir.join("c.tmpl"), "$Q $P\n").unwrap();
+        let path = |name: &str| dir.join(name).display().to_string();
+
+        let mut out = Vec::new();
+        cli(&["--list-vars", &path("a.tmpl"), &path("b.tmpl")]).run(&mut out).unwrap();
+        assert_eq!("NAME\nUSER_NAME\nGREETING\n", String::from_utf8(out).unwrap());
+
+        // Bash 中 `${(U)LOUD}` 不是变量引用
+        std::fs::write(dir.join("d.tmpl"), "${(U)LOUD} $HOME\n").unwrap();
+        let mut out = Vec::new();
+        cli(&["--list-vars", "--dialect", "bash", &path("d.tmpl")]).run(&mut out).unwrap();
+        assert_eq!("HOME\n", String::from_utf8(out).unwrap());
+
+        let mut out = Vec::new();
+        let args = ["--env-file", &path("base.env"), "--env-file", &path("local.env"), &path("b.tmpl")];
+        cli(&args).run(&mut out).unwrap();
+        assert_eq!("hi, hi-local\n", String::from_utf8(out).unwrap());
+
+        // 单引号中的值是字面量，展开结果不会再被展开
+        let mut out = Vec::new();
+        let args = ["--env-file", &path("base.env"), "--env-file", &path("local.env"), &path("c.tmpl")];
+        cli(&args).run(&mut out).unwrap();
+        assert_eq!("x$HOME pa$$word\n", String::from_utf8(out).unwrap());
+
+        let missing = cli(&[&path("missing.tmpl")]).run(&mut Vec::new()).unwrap_err();
+        assert!(missing.starts_with(&path("missing.tmpl")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+use crate::env_source::EnvSource;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::ops::Range;
+
+/// 返回 `OsString` 的环境变量查找源 trait，用于值可能不是合法 UTF-8 的场景（例如文件路径）。
+/// Trait for abstracting environment variable lookup with `OsString` values.
+///
+/// 变量名仍然是 `&str`：`$NAME` 中的名字只能由字母、数字与下划线组成。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::expand_env_os;
+/// use rush_var::os_env_source::Utf8Source;
+/// use std::ffi::OsStr;
+/// let env = Utf8Source([("HOME", "/home/me")]);
+/// assert_eq!(expand_env_os(OsStr::new("$HOME/.zshrc"), &env), "/home/me/.zshrc");
+/// ```
+pub trait OsEnvSource {
+    /// 获取指定 key 的变量值，如果不存在则返回 None。
+    fn get_os(&self, key: &str) -> Option<Cow<'_, OsStr>>;
+}
+
+/// 为任意已实现 OsEnvSource 的类型的引用自动实现 OsEnvSource
+impl<T: OsEnvSource + ?Sized> OsEnvSource for &T {
+    fn get_os(&self, key: &str) -> Option<Cow<'_, OsStr>> {
+        (**self).get_os(key)
+    }
+}
+
+/// HashMap（变量名 -> OsString）作为环境变量源
+impl OsEnvSource for HashMap<String, OsString> {
+    fn get_os(&self, key: &str) -> Option<Cow<'_, OsStr>> {
+        self.get(key).map(|value| Cow::Borrowed(value.as_os_str()))
+    }
+}
+
+/// 系统环境变量，保留非 UTF-8 的值
+impl OsEnvSource for std::env::VarsOs {
+    fn get_os(&self, key: &str) -> Option<Cow<'_, OsStr>> {
+        std::env::var_os(key).map(Cow::Owned)
+    }
+}
+
+/// 把任意 [`EnvSource`] 适配为 [`OsEnvSource`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Utf8Source<S>(pub S);
+
+impl<S: EnvSource> OsEnvSource for Utf8Source<S> {
+    fn get_os(&self, key: &str) -> Option<Cow<'_, OsStr>> {
+        self.0.get(key).map(|value| match value {
+            Cow::Borrowed(value) => Cow::Borrowed(OsStr::new(value)),
+            Cow::Owned(value) => Cow::Owned(OsString::from(value)),
+        })
+    }
+}
+
+/// 非 UTF-8 字节（均 >= 0x80）映射到 `ESCAPE + 字节` 的私用区字符，使展开器可以在 `&str` 上处理任意字节；
+/// 输入中本身落在该区间的字符以 `ESCAPE` 前缀转义，保证可以无损还原。
+const ESCAPE: u32 = 0x10FF00;
+
+fn is_reserved(c: char) -> bool {
+    (ESCAPE..=ESCAPE + 0xFF).contains(&(c as u32))
+}
+
+pub(crate) fn encode(input: &OsStr) -> Cow<'_, str> {
+    if let Some(input) = input.to_str()
+        && !input.chars().any(is_reserved)
+    {
+        return Cow::Borrowed(input);
+    }
+    let mut encoded = String::with_capacity(input.len());
+    for chunk in input.as_encoded_bytes().utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if is_reserved(c) {
+                encoded.push(char::from_u32(ESCAPE).expect("私用区字符合法"));
+            }
+            encoded.push(c);
+        }
+        for byte in chunk.invalid() {
+            encoded.push(char::from_u32(ESCAPE + u32::from(*byte)).expect("私用区字符合法"));
+        }
+    }
+    Cow::Owned(encoded)
+}
+
+pub(crate) fn decode(encoded: &str) -> OsString {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut chars = encoded.chars();
+    while let Some(c) = chars.next() {
+        let c = match (c as u32).checked_sub(ESCAPE) {
+            // 转义的保留字符按原样还原
+            Some(0) => match chars.next() {
+                Some(c) => c,
+                None => break,
+            },
+            Some(byte) if byte <= 0xFF => {
+                bytes.push(byte as u8);
+                continue;
+            }
+            _ => c,
+        };
+        bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+    bytes_to_os_string(bytes)
+}
+
+#[cfg(unix)]
+fn bytes_to_os_string(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+#[cfg(not(unix))]
+fn bytes_to_os_string(bytes: Vec<u8>) -> OsString {
+    OsString::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// 把编码后文本中的字节区间换算为原始输入中的字节区间
+pub(crate) fn original_span(encoded: &str, span: Range<usize>) -> Range<usize> {
+    let offset = |end: usize| {
+        let mut original = 0;
+        let mut escaped = false;
+        for c in encoded[..end.min(encoded.len())].chars() {
+            original += match (c as u32).checked_sub(ESCAPE) {
+                Some(0) if !escaped => {
+                    escaped = true;
+                    continue;
+                }
+                Some(byte) if byte <= 0xFF && !escaped => 1,
+                _ => c.len_utf8(),
+            };
+            escaped = false;
+        }
+        original
+    };
+    offset(span.start)..offset(span.end)
+}
+
+/// 把 [`OsEnvSource`] 的值编码后交给展开器
+pub(crate) struct Encoded<'a, S: ?Sized>(pub &'a S);
+
+impl<S: OsEnvSource + ?Sized> EnvSource for Encoded<'_, S> {
+    fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        let value = self.0.get_os(key)?;
+        Some(Cow::Owned(encode(&value).into_owned()))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[test]
+    fn test_encode_round_trip() {
+        let reserved = char::from_u32(ESCAPE + 0x41).unwrap();
+        let input = [b"a\xff/".as_slice(), format!("{reserved}").as_bytes(), b"\xc3$"].concat();
+        let input = OsStr::from_bytes(&input);
+        let encoded = encode(input);
+        assert_eq!(decode(&encoded), input);
+        assert_eq!(original_span(&encoded, 0..encoded.len()), 0..input.len());
+        assert_eq!(encode(OsStr::new("plain")), Cow::Borrowed("plain"));
+    }
+}
@@ -0,0 +1,198 @@
+//! Bash 风格的通配符匹配，用于 `${VAR#pat}`、`${VAR/pat/rep}` 等操作。
+//!
+//! 支持 `*`、`?`、`[...]`（含 `!`/`^` 取反、`a-z` 区间与 `[:alpha:]` 等字符类）以及 `\` 转义。
+
+#[derive(Debug, Clone)]
+enum Token {
+    Star,
+    Any,
+    Class { negated: bool, items: Vec<ClassItem> },
+    Literal(char),
+}
+
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Char(char),
+    Range(char, char),
+    Named(fn(char) -> bool),
+}
+
+/// 编译后的通配符模式
+#[derive(Debug, Clone)]
+pub(crate) struct Pattern {
+    tokens: Vec<Token>,
+}
+
+impl Pattern {
+    pub(crate) fn new(pattern: &str) -> Self {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '*' => {
+                    // 连续的 * 等价于一个
+                    if !matches!(tokens.last(), Some(Token::Star)) {
+                        tokens.push(Token::Star);
+                    }
+                    i += 1;
+                }
+                '?' => {
+                    tokens.push(Token::Any);
+                    i += 1;
+                }
+                '\\' if i + 1 < chars.len() => {
+                    tokens.push(Token::Literal(chars[i + 1]));
+                    i += 2;
+                }
+                '[' => match Self::parse_class(&chars, i + 1) {
+                    Some((token, next)) => {
+                        tokens.push(token);
+                        i = next;
+                    }
+                    None => {
+                        tokens.push(Token::Literal('['));
+                        i += 1;
+                    }
+                },
+                ch => {
+                    tokens.push(Token::Literal(ch));
+                    i += 1;
+                }
+            }
+        }
+        Pattern { tokens }
+    }
+
+    /// 解析 `[` 之后的字符类，返回 token 与 `]` 之后的位置；没有闭合的 `]` 时返回 None
+    fn parse_class(chars: &[char], mut i: usize) -> Option<(Token, usize)> {
+        let negated = matches!(chars.get(i), Some('!') | Some('^'));
+        if negated {
+            i += 1;
+        }
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let ch = *chars.get(i)?;
+            if ch == ']' && !first {
+                return Some((Token::Class { negated, items }, i + 1));
+            }
+            first = false;
+            if ch == '[' && chars.get(i + 1) == Some(&':') {
+                let rest: String = chars[i + 2..].iter().collect();
+                if let Some(end) = rest.find(":]")
+                    && let Some(named) = Self::named_class(&rest[..end])
+                {
+                    items.push(ClassItem::Named(named));
+                    i += 2 + rest[..end].chars().count() + 2;
+                    continue;
+                }
+            }
+            let ch = if ch == '\\' && i + 1 < chars.len() {
+                i += 1;
+                chars[i]
+            } else {
+                ch
+            };
+            if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|end| *end != ']') {
+                items.push(ClassItem::Range(ch, chars[i + 2]));
+                i += 3;
+            } else {
+                items.push(ClassItem::Char(ch));
+                i += 1;
+            }
+        }
+    }
+
+    fn named_class(name: &str) -> Option<fn(char) -> bool> {
+        Some(match name {
+            "alpha" => |c: char| c.is_alphabetic(),
+            "digit" => |c: char| c.is_ascii_digit(),
+            "alnum" => |c: char| c.is_alphanumeric(),
+            "upper" => |c: char| c.is_uppercase(),
+            "lower" => |c: char| c.is_lowercase(),
+            "space" => |c: char| c.is_whitespace(),
+            "blank" => |c: char| c == ' ' || c == '\t',
+            "punct" => |c: char| c.is_ascii_punctuation(),
+            "xdigit" => |c: char| c.is_ascii_hexdigit(),
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// 整个 `text` 是否与模式匹配
+    pub(crate) fn matches(&self, text: &[char]) -> bool {
+        let tokens = &self.tokens;
+        let (mut t, mut p) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+        while t < text.len() {
+            if p < tokens.len() {
+                match &tokens[p] {
+                    Token::Star => {
+                        backtrack = Some((p, t));
+                        p += 1;
+                        continue;
+                    }
+                    token if Self::matches_char(token, text[t]) => {
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            }
+        }
+        tokens[p..].iter().all(|token| matches!(token, Token::Star))
+    }
+
+    fn matches_char(token: &Token, ch: char) -> bool {
+        match token {
+            Token::Star => true,
+            Token::Any => true,
+            Token::Literal(literal) => *literal == ch,
+            Token::Class { negated, items } => {
+                let found = items.iter().any(|item| match item {
+                    ClassItem::Char(c) => *c == ch,
+                    ClassItem::Range(start, end) => (*start..=*end).contains(&ch),
+                    ClassItem::Named(predicate) => predicate(ch),
+                });
+                found != *negated
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Pattern::new(pattern).matches(&text.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(matches("*.txt", "a.txt"));
+        assert!(matches("a?c", "abc"));
+        assert!(!matches("a?c", "ac"));
+        assert!(matches("[a-c]x", "bx"));
+        assert!(!matches("[!a-c]x", "bx"));
+        assert!(matches("[[:digit:]]*", "1abc"));
+        assert!(matches(r"\*", "*"));
+        assert!(!matches(r"\*", "a"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("a*b*c", "axxbyyc"));
+        assert!(matches("[", "["));
+    }
+}
@@ -0,0 +1,300 @@
+//! serde 集成（需要开启 `serde` feature）：在反序列化时展开配置中的变量。
+//!
+//! - [`Expanded<T>`]：把字符串展开后再用 [`FromStr`] 解析为 `T`，例如 `port = "${PORT:-8080}"` 得到 `u16`
+//! - [`expand`] / [`expand_option`]：供 `#[serde(deserialize_with = "...")]` 使用，字段类型保持不变
+//! - [`Template`]：保留原始文本，序列化时原样写回，需要时再展开
+//!
+//! 反序列化期间从当前线程通过 [`with_env`] 设置的变量源中查找变量，未设置时使用进程环境变量。
+//! 展开是递归的（同 [`crate::try_expand_recursive`]），未闭合的 `${`、`${VAR:?msg}` 等错误会作为反序列化错误返回。
+//!
+//! # 用法示例
+//! ```rust
+//! use rush_var::serde::{Expanded, Template, with_env};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Config {
+//!     port: Expanded<u16>,
+//!     #[serde(deserialize_with = "rush_var::serde::expand")]
+//!     host: String,
+//!     log: Template,
+//! }
+//!
+//! let json = r#"{ "port": "${PORT:-8080}", "host": "$HOST", "log": "$HOME/app.log" }"#;
+//! let config: Config = with_env([("HOST", "localhost")], || serde_json::from_str(json)).unwrap();
+//! assert_eq!(*config.port, 8080);
+//! assert_eq!(config.host, "localhost");
+//! assert_eq!(config.log.as_str(), "$HOME/app.log");
+//! assert_eq!(config.log.expand(&[("HOME", "/home/me")]), "/home/me/app.log");
+//! ```
+
+use crate::env_source::EnvSource;
+use crate::error::ExpandError;
+use crate::{ExpandOptions, try_expand_recursive_with};
+use ::serde::de::Error as _;
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::RefCell;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use std::str::FromStr;
+
+thread_local! {
+    static ENV: RefCell<Option<Rc<dyn EnvSource>>> = const { RefCell::new(None) };
+}
+
+/// 在 `f` 执行期间（仅限当前线程）以 `env` 作为反序列化时展开变量的来源，可以嵌套，返回后恢复之前的来源
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::serde::{Expanded, with_env};
+/// let dir: Expanded<String> = with_env([("DIR", "/opt")], || serde_json::from_str(r#""$DIR/bin""#)).unwrap();
+/// assert_eq!(dir.into_inner(), "/opt/bin");
+/// ```
+pub fn with_env<R>(env: impl EnvSource + 'static, f: impl FnOnce() -> R) -> R {
+    /// 即使 `f` panic 也恢复之前的来源
+    struct Restore(Option<Rc<dyn EnvSource>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            ENV.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let previous = ENV.with(|current| current.replace(Some(Rc::new(env))));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// 用当前线程的变量源递归展开 `input`，未通过 [`with_env`] 设置时使用进程环境变量
+fn expand_current(input: &str) -> Result<String, ExpandError> {
+    let options = ExpandOptions::default();
+    match ENV.with(|current| current.borrow().clone()) {
+        Some(env) => try_expand_recursive_with(input, &&*env, &options),
+        None => try_expand_recursive_with(input, &std::env::vars(), &options),
+    }
+}
+
+fn expand_str<'de, D: Deserializer<'de>>(raw: &str) -> Result<String, D::Error> {
+    expand_current(raw).map_err(|error| D::Error::custom(format!("cannot expand {raw:?}: {error}")))
+}
+
+/// 反序列化时先展开字符串中的变量，再解析为 `T`；序列化时写出 `T` 本身
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Expanded<T>(pub T);
+
+impl<T> Expanded<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Expanded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Expanded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Expanded<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        let expanded = expand_str::<D>(&raw)?;
+        expanded
+            .parse()
+            .map(Expanded)
+            .map_err(|error| D::Error::custom(format!("invalid value {expanded:?} (expanded from {raw:?}): {error}")))
+    }
+}
+
+impl<T: Serialize> Serialize for Expanded<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// 用于 `#[serde(deserialize_with = "rush_var::serde::expand")]`，同 [`Expanded<T>`]，但字段直接是 `T`
+pub fn expand<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Expanded::deserialize(deserializer).map(Expanded::into_inner)
+}
+
+/// [`expand`] 的 `Option<T>` 版本，字段通常还需要 `#[serde(default)]`
+pub fn expand_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Option::<Expanded<T>>::deserialize(deserializer).map(|value| value.map(Expanded::into_inner))
+}
+
+/// 保留原始文本的模板：反序列化与序列化都使用原始文本，只在调用 `expand` 系列方法时展开。
+///
+/// # 用法示例
+/// ```rust
+/// use rush_var::serde::Template;
+/// let template: Template = serde_json::from_str(r#""${GREETING:-hello}, $USER""#).unwrap();
+/// assert_eq!(template.expand(&[("USER", "alice")]), "hello, alice");
+/// assert_eq!(serde_json::to_string(&template).unwrap(), r#""${GREETING:-hello}, $USER""#);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Template {
+    raw: String,
+}
+
+impl Template {
+    pub fn new(raw: impl Into<String>) -> Self {
+        Self { raw: raw.into() }
+    }
+
+    /// 原始文本
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// 宽松地递归展开，同 [`crate::expand_env_recursive`]
+    pub fn expand(&self, env: &impl EnvSource) -> String {
+        crate::expand_env_recursive(&self.raw, env)
+    }
+
+    /// 同 [`crate::try_expand_recursive`]
+    pub fn try_expand(&self, env: &impl EnvSource) -> Result<String, ExpandError> {
+        crate::try_expand_recursive(&self.raw, env)
+    }
+
+    /// 同 [`crate::try_expand_recursive_with`]
+    pub fn try_expand_with(&self, env: &impl EnvSource, options: &ExpandOptions) -> Result<String, ExpandError> {
+        try_expand_recursive_with(&self.raw, env, options)
+    }
+
+    /// 用当前线程通过 [`with_env`] 设置的变量源展开，未设置时使用进程环境变量
+    pub fn resolve(&self) -> Result<String, ExpandError> {
+        expand_current(&self.raw)
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl From<&str> for Template {
+    fn from(raw: &str) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl From<String> for Template {
+    fn from(raw: String) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for Template {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+impl Serialize for Template {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env_stack::EnvStack;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Config {
+        port: Expanded<u16>,
+        #[serde(deserialize_with = "expand")]
+        dir: String,
+        #[serde(deserialize_with = "expand_option", default)]
+        timeout: Option<u32>,
+        command: Template,
+    }
+
+    #[test]
+    fn test_deserialize_with_seeded_env() {
+        let json = r#"{ "port": "${PORT:-8080}", "dir": "$ROOT/bin", "timeout": "${TIMEOUT}0", "command": "$EDITOR $ROOT" }"#;
+        let mut stack = EnvStack::new();
+        stack.set("ROOT", "$HOME/app");
+        stack.set("HOME", "/home/me");
+        stack.set("TIMEOUT", "3");
+        let config: Config = with_env(stack, || serde_json::from_str(json)).unwrap();
+        assert_eq!(
+            Config {
+                port: Expanded(8080),
+                dir: "/home/me/app/bin".to_string(),
+                timeout: Some(30),
+                command: Template::new("$EDITOR $ROOT"),
+            },
+            config
+        );
+        assert_eq!(
+            r#"{"port":8080,"dir":"/home/me/app/bin","timeout":30,"command":"$EDITOR $ROOT"}"#,
+            serde_json::to_string(&config).unwrap()
+        );
+        assert_eq!(
+            "vim /home/me/app",
+            with_env([("EDITOR", "vim"), ("ROOT", "/home/me/app")], || config.command.resolve()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors_and_nesting() {
+        let port = |json: &str| {
+            with_env([("PORT", "http")], || {
+                serde_json::from_str::<Expanded<u16>>(json).map_err(|e| e.to_string())
+            })
+        };
+        assert_eq!(
+            Err(r#"invalid value "http" (expanded from "$PORT"): invalid digit found in string"#.to_string()),
+            port(r#""$PORT""#)
+        );
+        assert!(
+            port(r#""${PORT""#)
+                .unwrap_err()
+                .starts_with(r#"cannot expand "${PORT": unterminated '${'"#)
+        );
+
+        let nested = with_env([("A", "outer")], || {
+            let inner = with_env([("A", "inner")], || Template::from("$A").resolve());
+            (inner, Template::from("$A").resolve())
+        });
+        assert_eq!((Ok("inner".to_string()), Ok("outer".to_string())), nested);
+    }
+}